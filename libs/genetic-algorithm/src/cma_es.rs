@@ -0,0 +1,266 @@
+use crate::*;
+use nalgebra::{DMatrix, DVector, SymmetricEigen};
+
+/// CmaEs represents the Covariance Matrix Adaptation Evolution Strategy, sampling chromosomes
+/// from a multivariate normal distribution whose mean, step size and covariance are adapted
+/// towards the fittest samples.
+///
+/// Works on continuous chromosomes such as brain weights, through an ask/tell interface: `ask`
+/// samples a population, which is evaluated before being passed to `tell`.
+pub struct CmaEs {
+    population_size: usize, // Lambda, number of samples per generation
+    weights: Vec<f32>,      // Recombination weights of the fittest half, summing to 1
+    mueff: f32,             // Variance effective selection mass
+    cc: f32,                // Time constant of the covariance path
+    cs: f32,                // Time constant of the step size path
+    c1: f32,                // Learning rate of the rank-one update
+    cmu: f32,               // Learning rate of the rank-mu update
+    damps: f32,             // Damping of the step size
+    chi_n: f32,             // Expected norm of a standard normal vector
+
+    mean: DVector<f32>,
+    sigma: f32,
+    pc: DVector<f32>, // Evolution path of the covariance
+    ps: DVector<f32>, // Evolution path of the step size
+    c: DMatrix<f32>,  // Covariance matrix
+    b: DMatrix<f32>,  // Eigenvectors of the covariance, as columns
+    d: DVector<f32>,  // Square roots of the covariance eigenvalues
+    inv_sqrt_c: DMatrix<f32>,
+    evaluations: usize,
+    eigen_evaluations: usize, // Evaluations at the last decomposition
+    generation: usize,
+}
+
+impl CmaEs {
+    /// Creates a strategy centered on given chromosome, with an initial step size and a number
+    /// of samples per generation.
+    pub fn new(mean: &Chromosome, sigma: f32, population_size: usize) -> Self {
+        assert!(!mean.is_empty());
+        assert!(sigma > 0.0);
+        assert!(population_size >= 2);
+
+        let n = mean.len() as f32;
+        let mu = population_size / 2;
+        let raw: Vec<f32> = (1..=mu)
+            .map(|i| ((mu as f32 + 0.5).ln() - (i as f32).ln()).max(0.0))
+            .collect();
+        let sum: f32 = raw.iter().sum();
+        let weights: Vec<f32> = raw.iter().map(|w| w / sum).collect();
+        let mueff = 1.0 / weights.iter().map(|w| w * w).sum::<f32>();
+
+        let cc = (4.0 + mueff / n) / (n + 4.0 + 2.0 * mueff / n);
+        let cs = (mueff + 2.0) / (n + mueff + 5.0);
+        let c1 = 2.0 / ((n + 1.3).powi(2) + mueff);
+        let cmu = (1.0 - c1).min(2.0 * (mueff - 2.0 + 1.0 / mueff) / ((n + 2.0).powi(2) + mueff));
+        let damps = 1.0 + 2.0 * (((mueff - 1.0) / (n + 1.0)).sqrt() - 1.0).max(0.0) + cs;
+        let chi_n = n.sqrt() * (1.0 - 1.0 / (4.0 * n) + 1.0 / (21.0 * n * n));
+
+        let dim = mean.len();
+        Self {
+            population_size,
+            weights,
+            mueff,
+            cc,
+            cs,
+            c1,
+            cmu,
+            damps,
+            chi_n,
+            mean: DVector::from_iterator(dim, mean.iter().copied()),
+            sigma,
+            pc: DVector::zeros(dim),
+            ps: DVector::zeros(dim),
+            c: DMatrix::identity(dim, dim),
+            b: DMatrix::identity(dim, dim),
+            d: DVector::from_element(dim, 1.0),
+            inv_sqrt_c: DMatrix::identity(dim, dim),
+            evaluations: 0,
+            eigen_evaluations: 0,
+            generation: 0,
+        }
+    }
+
+    /// Returns the current mean of the distribution, the best guess so far.
+    pub fn mean(&self) -> Chromosome {
+        self.mean.iter().copied().collect()
+    }
+
+    /// Returns the current step size.
+    pub fn sigma(&self) -> f32 {
+        self.sigma
+    }
+
+    /// Returns the number of generations told so far.
+    pub fn generation(&self) -> usize {
+        self.generation
+    }
+
+    /// Samples a new population from the current distribution.
+    pub fn ask(&self, rng: &mut dyn RngCore) -> Vec<Chromosome> {
+        (0..self.population_size)
+            .map(|_| {
+                let z = DVector::from_fn(self.mean.len(), |_, _| standard_normal(rng));
+                let y = &self.b * z.component_mul(&self.d);
+                (&self.mean + y * self.sigma).iter().copied().collect()
+            })
+            .collect()
+    }
+
+    /// Adapts the distribution towards the fittest individuals of an evaluated population.
+    pub fn tell<I>(&mut self, population: &[I])
+    where
+        I: Individual,
+    {
+        assert!(population.len() >= self.weights.len());
+
+        let mut ranked: Vec<&I> = population.iter().collect();
+        ranked.sort_by(|a, b| b.fitness().total_cmp(&a.fitness()));
+        let selected: Vec<DVector<f32>> = ranked
+            .iter()
+            .take(self.weights.len())
+            .map(|individual| {
+                let chromosome = individual.chromosome();
+                assert_eq!(chromosome.len(), self.mean.len());
+                DVector::from_iterator(chromosome.len(), chromosome.iter().copied())
+            })
+            .collect();
+
+        self.evaluations += population.len();
+        self.generation += 1;
+        let n = self.mean.len() as f32;
+
+        // Moving the mean towards the weighted recombination of the fittest.
+        let old_mean = self.mean.clone();
+        self.mean = selected
+            .iter()
+            .zip(&self.weights)
+            .fold(DVector::zeros(old_mean.len()), |mean, (x, &w)| mean + x * w);
+        let y = (&self.mean - &old_mean) / self.sigma;
+
+        // Cumulating the evolution paths.
+        self.ps = &self.ps * (1.0 - self.cs)
+            + &self.inv_sqrt_c * &y * (self.cs * (2.0 - self.cs) * self.mueff).sqrt();
+        let generations = self.evaluations as f32 / self.population_size as f32;
+        let ps_norm = self.ps.norm() / (1.0 - (1.0 - self.cs).powf(2.0 * generations)).sqrt();
+        let hsig = ps_norm / self.chi_n < 1.4 + 2.0 / (n + 1.0);
+        let hsig = if hsig { 1.0 } else { 0.0 };
+        self.pc = &self.pc * (1.0 - self.cc)
+            + &y * (hsig * (self.cc * (2.0 - self.cc) * self.mueff).sqrt());
+
+        // Adapting the covariance with the rank-one and rank-mu updates.
+        let rank_one =
+            &self.pc * self.pc.transpose() + &self.c * ((1.0 - hsig) * self.cc * (2.0 - self.cc));
+        let rank_mu = selected.iter().zip(&self.weights).fold(
+            DMatrix::zeros(old_mean.len(), old_mean.len()),
+            |rank_mu, (x, &w)| {
+                let step = (x - &old_mean) / self.sigma;
+                rank_mu + &step * step.transpose() * w
+            },
+        );
+        self.c = &self.c * (1.0 - self.c1 - self.cmu) + rank_one * self.c1 + rank_mu * self.cmu;
+
+        // Adapting the step size by comparing the path length to its expectation.
+        self.sigma *= ((self.cs / self.damps) * (self.ps.norm() / self.chi_n - 1.0)).exp();
+
+        // Decomposing the covariance lazily, as it is the costliest step.
+        let interval = self.population_size as f32 / (self.c1 + self.cmu) / n / 10.0;
+        if (self.evaluations - self.eigen_evaluations) as f32 > interval {
+            self.decompose();
+        }
+    }
+
+    /// Tells the evaluated population and asks for the next one, in the same shape as
+    /// [`GeneticAlgorithm::evolve`].
+    pub fn evolve<I>(&mut self, rng: &mut dyn RngCore, population: &[I]) -> (Vec<I>, Statistics)
+    where
+        I: Individual,
+    {
+        self.tell(population);
+        let new_population = self.ask(rng).into_iter().map(I::create).collect();
+        (new_population, Statistics::new(population))
+    }
+
+    fn decompose(&mut self) {
+        self.eigen_evaluations = self.evaluations;
+
+        // Enforcing symmetry against rounding errors.
+        let c = (&self.c + self.c.transpose()) * 0.5;
+        let eigen = SymmetricEigen::new(c.clone());
+        self.c = c;
+        self.d = eigen
+            .eigenvalues
+            .map(|value| value.max(f32::EPSILON).sqrt());
+        self.b = eigen.eigenvectors;
+
+        let inv_d = DMatrix::from_diagonal(&self.d.map(|d| 1.0 / d));
+        self.inv_sqrt_c = &self.b * inv_d * self.b.transpose();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    #[derive(Debug)]
+    struct TestIndividual {
+        chromosome: Chromosome,
+    }
+
+    impl Individual for TestIndividual {
+        fn create(chromosome: Chromosome) -> Self {
+            Self { chromosome }
+        }
+        // Maximized at (1, -2, 3), on an ill-conditioned landscape.
+        fn fitness(&self) -> f32 {
+            let target = [1.0, -2.0, 3.0];
+            let scales = [1.0, 10.0, 100.0];
+            -self
+                .chromosome
+                .iter()
+                .zip(target.iter().zip(scales))
+                .map(|(x, (t, s))| s * (x - t).powi(2))
+                .sum::<f32>()
+        }
+        fn chromosome(&self) -> &Chromosome {
+            &self.chromosome
+        }
+    }
+
+    #[test]
+    fn converges_on_ill_conditioned_landscape() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let start: Chromosome = vec![0.0, 0.0, 0.0].into_iter().collect();
+        let mut cma = CmaEs::new(&start, 1.0, 12);
+
+        for _ in 0..150 {
+            let population: Vec<TestIndividual> = cma
+                .ask(&mut rng)
+                .into_iter()
+                .map(TestIndividual::create)
+                .collect();
+            cma.tell(&population);
+        }
+
+        let mean = cma.mean();
+        assert_eq!(cma.generation(), 150);
+        for (gene, target) in mean.iter().zip([1.0, -2.0, 3.0]) {
+            approx::assert_relative_eq!(*gene, target, epsilon = 1e-3);
+        }
+        assert!(cma.sigma() < 0.01);
+    }
+
+    #[test]
+    fn ask_samples_around_the_mean() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let start: Chromosome = vec![5.0; 4].into_iter().collect();
+        let cma = CmaEs::new(&start, 0.1, 100);
+
+        let samples = cma.ask(&mut rng);
+        assert_eq!(samples.len(), 100);
+
+        let average = samples.iter().flat_map(|s| s.iter()).sum::<f32>() / 400.0;
+        approx::assert_relative_eq!(average, 5.0, epsilon = 0.02);
+    }
+}
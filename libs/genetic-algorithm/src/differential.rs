@@ -0,0 +1,196 @@
+use crate::*;
+use rand::seq::index;
+
+/// DifferentialVariant selects the base vector mutated by differential evolution.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DifferentialVariant {
+    /// DE/rand/1/bin, mutating a random target.
+    Rand1Bin,
+    /// DE/best/1/bin, mutating the fittest target.
+    Best1Bin,
+}
+
+/// DifferentialEvolution represents an evolution strategy mutating targets by the scaled
+/// difference of two other ones, then crossing the mutant over with its target.
+///
+/// Each evaluated trial replaces its target when at least as fit, so that the targets only ever
+/// improve from one generation to the next.
+pub struct DifferentialEvolution {
+    variant: DifferentialVariant,
+    f: f32,  // Differential weight, scaling the difference vector
+    cr: f32, // Crossover probability by range [0, 1]
+    targets: Vec<(Chromosome, f32)>,
+}
+
+impl DifferentialEvolution {
+    pub fn new(variant: DifferentialVariant, f: f32, cr: f32) -> Self {
+        assert!(f > 0.0);
+        assert!((0.0..=1.0).contains(&cr));
+
+        Self {
+            variant,
+            f,
+            cr,
+            targets: Vec::new(),
+        }
+    }
+
+    /// Returns the targets along with their fitness.
+    pub fn targets(&self) -> &[(Chromosome, f32)] {
+        &self.targets
+    }
+
+    /// Replaces the targets with the evaluated trials at least as fit. The first population
+    /// becomes the targets as a whole.
+    pub fn tell<I>(&mut self, trials: &[I])
+    where
+        I: Individual,
+    {
+        if self.targets.is_empty() {
+            self.targets = trials
+                .iter()
+                .map(|trial| (trial.chromosome().clone(), trial.fitness()))
+                .collect();
+            return;
+        }
+
+        assert_eq!(trials.len(), self.targets.len());
+        for (target, trial) in self.targets.iter_mut().zip(trials) {
+            if trial.fitness() >= target.1 {
+                *target = (trial.chromosome().clone(), trial.fitness());
+            }
+        }
+    }
+
+    /// Returns one trial chromosome per target.
+    pub fn ask(&self, rng: &mut dyn RngCore) -> Vec<Chromosome> {
+        let len = self.targets.len();
+        assert!(len >= 4, "got too few targets for differential evolution");
+
+        let best = self
+            .targets
+            .iter()
+            .enumerate()
+            .max_by(|(_, a), (_, b)| a.1.total_cmp(&b.1))
+            .map(|(i, _)| i)
+            .expect("got no target");
+
+        (0..len)
+            .map(|i| {
+                // Three distinct targets other than the current one.
+                let others: Vec<usize> = index::sample(rng, len, 4)
+                    .into_iter()
+                    .filter(|&r| r != i)
+                    .take(3)
+                    .collect();
+                let base = match self.variant {
+                    DifferentialVariant::Rand1Bin => others[0],
+                    DifferentialVariant::Best1Bin => best,
+                };
+                let (base, a, b) = (
+                    &self.targets[base].0,
+                    &self.targets[others[1]].0,
+                    &self.targets[others[2]].0,
+                );
+                let target = &self.targets[i].0;
+
+                // At least one gene comes from the mutant.
+                let forced = rng.gen_range(0..target.len());
+                (0..target.len())
+                    .map(|gene| {
+                        if gene == forced || rng.gen_bool(self.cr as f64) {
+                            base[gene] + self.f * (a[gene] - b[gene])
+                        } else {
+                            target[gene]
+                        }
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Tells the evaluated trials and asks for the next ones, in the same shape as
+    /// [`GeneticAlgorithm::evolve`].
+    pub fn evolve<I>(&mut self, rng: &mut dyn RngCore, trials: &[I]) -> (Vec<I>, Statistics)
+    where
+        I: Individual,
+    {
+        self.tell(trials);
+        let new_population = self.ask(rng).into_iter().map(I::create).collect();
+        (new_population, Statistics::new(trials))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    #[derive(Debug)]
+    struct TestIndividual {
+        chromosome: Chromosome,
+    }
+
+    impl Individual for TestIndividual {
+        fn create(chromosome: Chromosome) -> Self {
+            Self { chromosome }
+        }
+        // Maximized at every gene equal to 0.5.
+        fn fitness(&self) -> f32 {
+            -self
+                .chromosome
+                .iter()
+                .map(|x| (x - 0.5).powi(2))
+                .sum::<f32>()
+        }
+        fn chromosome(&self) -> &Chromosome {
+            &self.chromosome
+        }
+    }
+
+    fn optimize(variant: DifferentialVariant) -> f32 {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut de = DifferentialEvolution::new(variant, 0.6, 0.9);
+        let mut population: Vec<TestIndividual> = (0..20)
+            .map(|_| {
+                let genes = (0..5).map(|_| rng.gen_range(-5.0..5.0));
+                TestIndividual::create(genes.collect())
+            })
+            .collect();
+
+        for _ in 0..100 {
+            population = de.evolve(&mut rng, &population).0;
+        }
+        de.tell(&population);
+
+        de.targets()
+            .iter()
+            .map(|(_, fitness)| *fitness)
+            .fold(f32::MIN, f32::max)
+    }
+
+    #[test]
+    fn rand1bin() {
+        assert!(optimize(DifferentialVariant::Rand1Bin) > -1e-4);
+    }
+
+    #[test]
+    fn best1bin() {
+        assert!(optimize(DifferentialVariant::Best1Bin) > -1e-4);
+    }
+
+    #[test]
+    fn targets_never_worsen() {
+        let mut de = DifferentialEvolution::new(DifferentialVariant::Rand1Bin, 0.5, 0.9);
+        let good = TestIndividual::create(vec![0.5].into_iter().collect());
+        let bad = TestIndividual::create(vec![9.0].into_iter().collect());
+
+        de.tell(&[TestIndividual::create(vec![0.5].into_iter().collect())]);
+        de.tell(&[bad]);
+        assert_eq!(de.targets()[0].0[0], 0.5);
+
+        de.tell(&[good]);
+        assert_eq!(de.targets()[0].1, 0.0);
+    }
+}
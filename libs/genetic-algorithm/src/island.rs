@@ -0,0 +1,320 @@
+use crate::*;
+use rand::seq::index;
+
+/// MigrationTopology describes which islands receive the migrants of an island.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MigrationTopology {
+    /// Migrants move on to the next island, the last one sending to the first.
+    Ring,
+    /// Migrants are sent to every other island.
+    FullyConnected,
+}
+
+impl MigrationTopology {
+    fn destinations(&self, source: usize, islands: usize) -> Vec<usize> {
+        match self {
+            Self::Ring if islands > 1 => vec![(source + 1) % islands],
+            Self::Ring => Vec::new(),
+            Self::FullyConnected => (0..islands).filter(|&i| i != source).collect(),
+        }
+    }
+}
+
+/// IslandModel represents a wrapper running separated sub-populations, each evolved by its own
+/// genetic algorithm, which periodically exchange their best individuals.
+///
+/// Keeping sub-populations apart slows down the convergence to a single behaviour, while
+/// migration still spreads good solutions between islands.
+pub struct IslandModel<S, G = f32> {
+    islands: Vec<GeneticAlgorithm<S, G>>,
+    topology: MigrationTopology,
+    migration_interval: usize, // Migrating every N generations, never if zero
+    migrants: usize,           // Top-M individuals sent by each island
+    immigrants: Vec<usize>,    // Individuals taken in by each island on the last migration
+    generation: usize,
+}
+
+impl<S, G> IslandModel<S, G>
+where
+    S: SelectionMethod<G>,
+    G: Gene,
+{
+    /// Creates an island model with one genetic algorithm per island.
+    pub fn new(
+        islands: Vec<GeneticAlgorithm<S, G>>,
+        topology: MigrationTopology,
+        migration_interval: usize,
+        migrants: usize,
+    ) -> Self {
+        assert!(!islands.is_empty());
+
+        Self {
+            immigrants: vec![0; islands.len()],
+            islands,
+            topology,
+            migration_interval,
+            migrants,
+            generation: 0,
+        }
+    }
+
+    /// Returns the number of islands.
+    pub fn len(&self) -> usize {
+        self.islands.len()
+    }
+
+    /// Returns true if the model has no islands, which never happens once created.
+    pub fn is_empty(&self) -> bool {
+        self.islands.is_empty()
+    }
+
+    /// Returns the number of individuals each island took in on the last migration.
+    ///
+    /// An island receives `migrants` individuals from every island sending to it, but never
+    /// more than its own size: past it, immigrants of the last sending islands are left out.
+    pub fn immigrants(&self) -> &[usize] {
+        &self.immigrants
+    }
+
+    /// Returns the number of generations evolved so far.
+    pub fn generation(&self) -> usize {
+        self.generation
+    }
+
+    /// Evolves every island's population with its own genetic algorithm, then migrates copies of
+    /// the fittest individuals of the evaluated populations into the new ones, replacing random
+    /// offsprings.
+    pub fn evolve<I>(
+        &mut self,
+        rng: &mut dyn RngCore,
+        populations: &[Vec<I>],
+    ) -> (Vec<Vec<I>>, Vec<Statistics>)
+    where
+        I: Individual<G>,
+    {
+        let scores: Vec<Vec<f32>> = populations
+            .iter()
+            .map(|population| population.iter().map(|i| i.fitness()).collect())
+            .collect();
+        self.evolve_with_scores(rng, populations, &scores)
+    }
+
+    /// Same as [`IslandModel::evolve`], but selecting parents and migrants on given scores
+    /// instead of the individuals' fitness.
+    pub fn evolve_with_scores<I>(
+        &mut self,
+        rng: &mut dyn RngCore,
+        populations: &[Vec<I>],
+        scores: &[Vec<f32>],
+    ) -> (Vec<Vec<I>>, Vec<Statistics>)
+    where
+        I: Individual<G>,
+    {
+        assert_eq!(populations.len(), self.islands.len());
+        assert_eq!(populations.len(), scores.len());
+
+        let (mut new_populations, stats): (Vec<_>, Vec<_>) = self
+            .islands
+            .iter_mut()
+            .zip(populations.iter().zip(scores))
+            .map(|(ga, (population, scores))| ga.evolve_with_scores(rng, population, scores))
+            .unzip();
+
+        self.generation += 1;
+        if self.migration_interval > 0 && self.generation.is_multiple_of(self.migration_interval) {
+            self.migrate(rng, populations, scores, &mut new_populations);
+        }
+
+        (new_populations, stats)
+    }
+
+    fn migrate<I>(
+        &mut self,
+        rng: &mut dyn RngCore,
+        populations: &[Vec<I>],
+        scores: &[Vec<f32>],
+        new_populations: &mut [Vec<I>],
+    ) where
+        I: Individual<G>,
+    {
+        let islands = populations.len();
+
+        // Gathering immigrants of each island before replacing anyone.
+        let mut immigrants: Vec<Vec<&Chromosome<G>>> = vec![Vec::new(); islands];
+        for (source, (population, scores)) in populations.iter().zip(scores).enumerate() {
+            let emigrants = fittest(population, scores, self.migrants);
+            for destination in self.topology.destinations(source, islands) {
+                immigrants[destination].extend(emigrants.iter().copied());
+            }
+        }
+
+        for ((population, immigrants), taken) in new_populations
+            .iter_mut()
+            .zip(immigrants)
+            .zip(&mut self.immigrants)
+        {
+            let amount = immigrants.len().min(population.len());
+            *taken = amount;
+            if amount == 0 {
+                continue;
+            }
+            let slots = index::sample(rng, population.len(), amount);
+            for (slot, chromosome) in slots.iter().zip(immigrants) {
+                population[slot] = I::create(chromosome.clone());
+            }
+        }
+    }
+}
+
+// Returns the chromosomes of the `count` best scored individuals, best first.
+fn fittest<'a, I, G>(population: &'a [I], scores: &[f32], count: usize) -> Vec<&'a Chromosome<G>>
+where
+    I: Individual<G>,
+{
+    let mut ranked: Vec<(&I, f32)> = population.iter().zip(scores.iter().copied()).collect();
+    ranked.sort_by(|(_, a), (_, b)| b.total_cmp(a));
+    ranked
+        .into_iter()
+        .take(count)
+        .map(|(individual, _)| individual.chromosome())
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    #[derive(Debug)]
+    struct TestIndividual {
+        chromosome: Chromosome,
+    }
+
+    impl Individual for TestIndividual {
+        fn create(chromosome: Chromosome) -> Self {
+            Self { chromosome }
+        }
+        fn fitness(&self) -> f32 {
+            self.chromosome.iter().sum()
+        }
+        fn chromosome(&self) -> &Chromosome {
+            &self.chromosome
+        }
+    }
+
+    fn population(genes: f32, size: usize) -> Vec<TestIndividual> {
+        (0..size)
+            .map(|i| TestIndividual::create(vec![genes, i as f32].into_iter().collect()))
+            .collect()
+    }
+
+    // No mutation, and crossover between identical islands keeps their genes apart.
+    fn ga() -> GeneticAlgorithm<RouletteWheelSelection> {
+        GeneticAlgorithm::new(
+            RouletteWheelSelection,
+            UniformCrossover,
+            GaussianMutation::new(0.0, 0.0),
+        )
+    }
+
+    fn island_genes(population: &[TestIndividual]) -> Vec<f32> {
+        population.iter().map(|i| i.chromosome()[0]).collect()
+    }
+
+    #[test]
+    fn topology_destinations() {
+        assert_eq!(MigrationTopology::Ring.destinations(2, 3), vec![0]);
+        assert_eq!(
+            MigrationTopology::Ring.destinations(0, 1),
+            Vec::<usize>::new()
+        );
+        assert_eq!(
+            MigrationTopology::FullyConnected.destinations(1, 3),
+            vec![0, 2]
+        );
+    }
+
+    #[test]
+    fn without_migration() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut model = IslandModel::new(vec![ga(), ga()], MigrationTopology::Ring, 2, 1);
+        let populations = vec![population(1.0, 4), population(100.0, 4)];
+
+        let (populations, stats) = model.evolve(&mut rng, &populations);
+
+        assert_eq!(model.generation(), 1);
+        assert_eq!(stats.len(), 2);
+        assert_eq!(island_genes(&populations[0]), vec![1.0; 4]);
+        assert_eq!(island_genes(&populations[1]), vec![100.0; 4]);
+    }
+
+    #[test]
+    fn ring_migration() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut model = IslandModel::new(vec![ga(), ga(), ga()], MigrationTopology::Ring, 1, 2);
+        let populations = vec![
+            population(1.0, 4),
+            population(10.0, 4),
+            population(100.0, 4),
+        ];
+
+        let (populations, _) = model.evolve(&mut rng, &populations);
+
+        // Each island received the top-2 of its predecessor in the ring.
+        let count = |island: usize, genes: f32| {
+            island_genes(&populations[island])
+                .into_iter()
+                .filter(|&g| g == genes)
+                .count()
+        };
+        assert_eq!(count(0, 100.0), 2);
+        assert_eq!(count(1, 1.0), 2);
+        assert_eq!(count(2, 10.0), 2);
+        assert_eq!(count(2, 100.0), 2);
+    }
+
+    #[test]
+    fn fully_connected_migration() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut model = IslandModel::new(
+            vec![ga(), ga(), ga()],
+            MigrationTopology::FullyConnected,
+            1,
+            1,
+        );
+        let populations = vec![
+            population(1.0, 4),
+            population(10.0, 4),
+            population(100.0, 4),
+        ];
+
+        let (populations, _) = model.evolve(&mut rng, &populations);
+
+        let mut genes = island_genes(&populations[0]);
+        genes.sort_by(f32::total_cmp);
+        assert_eq!(genes, vec![1.0, 1.0, 10.0, 100.0]);
+    }
+
+    #[test]
+    fn uneven_migration() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut model = IslandModel::new(
+            vec![ga(), ga(), ga()],
+            MigrationTopology::FullyConnected,
+            1,
+            2,
+        );
+        let populations = vec![
+            population(1.0, 4),
+            population(10.0, 2),
+            population(100.0, 4),
+        ];
+
+        let (populations, _) = model.evolve(&mut rng, &populations);
+
+        // The smaller island can't take in the 4 individuals sent to it.
+        assert_eq!(model.immigrants(), [4, 2, 4]);
+        assert_eq!(island_genes(&populations[1]), vec![1.0, 1.0]);
+    }
+}
@@ -14,33 +14,60 @@
 //! Reference code by [link](https://pwy.io/posts/learning-to-fly-pt1)
 //!
 
-use rand::{Rng, RngCore, seq::SliceRandom};
-use std::ops::Index;
+use rand::{
+    Rng, RngCore,
+    distributions::{Distribution, WeightedError, WeightedIndex},
+};
+use std::ops::{Index, IndexMut};
+
+mod cma_es;
+mod differential;
+mod island;
+mod map_elites;
+mod novelty;
+mod nsga;
+mod observer;
+mod operators;
+mod species;
+
+pub use self::{
+    cma_es::*, differential::*, island::*, map_elites::*, novelty::*, nsga::*, observer::*,
+    operators::*, species::*,
+};
 
 /// Chromosome represents the individual genes of a boid.
+///
+/// Genes default to `f32` weights, but can be of any type such as `bool` bit-strings, bounded
+/// integers or `usize` permutations.
 #[derive(Clone, Debug)]
-pub struct Chromosome {
-    genes: Vec<f32>,
+pub struct Chromosome<G = f32> {
+    genes: Vec<G>,
 }
 
-impl Index<usize> for Chromosome {
-    type Output = f32;
+impl<G> Index<usize> for Chromosome<G> {
+    type Output = G;
 
     fn index(&self, index: usize) -> &Self::Output {
         &self.genes[index]
     }
 }
 
-impl FromIterator<f32> for Chromosome {
-    fn from_iter<T: IntoIterator<Item = f32>>(iter: T) -> Self {
+impl<G> IndexMut<usize> for Chromosome<G> {
+    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
+        &mut self.genes[index]
+    }
+}
+
+impl<G> FromIterator<G> for Chromosome<G> {
+    fn from_iter<T: IntoIterator<Item = G>>(iter: T) -> Self {
         Self {
             genes: iter.into_iter().collect(),
         }
     }
 }
 
-impl IntoIterator for Chromosome {
-    type Item = f32;
+impl<G> IntoIterator for Chromosome<G> {
+    type Item = G;
     type IntoIter = std::vec::IntoIter<Self::Item>;
 
     fn into_iter(self) -> Self::IntoIter {
@@ -48,86 +75,178 @@ impl IntoIterator for Chromosome {
     }
 }
 
-impl Chromosome {
+impl<G> Chromosome<G> {
     /// Returns the length of gene slice.
     pub fn len(&self) -> usize {
         self.genes.len()
     }
+    /// Returns true if the chromosome contains no genes.
+    pub fn is_empty(&self) -> bool {
+        self.genes.is_empty()
+    }
     /// Returns the iterator of gene slice.
-    pub fn iter(&self) -> impl Iterator<Item = &f32> {
+    pub fn iter(&self) -> impl Iterator<Item = &G> {
         self.genes.iter()
     }
     /// Returns the mutable iterator of gene slice.
-    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut f32> {
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut G> {
         self.genes.iter_mut()
     }
+    /// Returns the gene slice.
+    pub fn genes(&self) -> &[G] {
+        &self.genes
+    }
+    /// Returns the mutable gene slice, for operators rearranging genes.
+    pub fn genes_mut(&mut self) -> &mut [G] {
+        &mut self.genes
+    }
+}
+
+impl<G> Chromosome<G>
+where
+    G: Gene,
+{
+    /// Returns the euclidean distance between both gene slices.
+    pub fn distance(&self, other: &Chromosome<G>) -> f32 {
+        assert_eq!(self.len(), other.len());
+
+        self.iter()
+            .zip(other.iter())
+            .map(|(a, b)| a.difference(b).powi(2))
+            .sum::<f32>()
+            .sqrt()
+    }
+}
+
+/// Gene describes types that chromosomes can be made of, measuring how far apart two genes are.
+pub trait Gene: Clone {
+    fn difference(&self, other: &Self) -> f32;
+}
+
+impl Gene for f32 {
+    fn difference(&self, other: &Self) -> f32 {
+        self - other
+    }
 }
 
+// Bit-strings compare by hamming distance.
+impl Gene for bool {
+    fn difference(&self, other: &Self) -> f32 {
+        if self == other { 0.0 } else { 1.0 }
+    }
+}
+
+macro_rules! impl_integer_gene {
+    ($($ty:ty),*) => {
+        $(impl Gene for $ty {
+            fn difference(&self, other: &Self) -> f32 {
+                *self as f32 - *other as f32
+            }
+        })*
+    };
+}
+
+impl_integer_gene!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+
 /// Individual describes types that exists in the population.
-pub trait Individual {
+pub trait Individual<G = f32> {
     // Rather use `create` to avoid confusion with struct's constructor naming convention, `new`
-    fn create(chromosome: Chromosome) -> Self;
+    fn create(chromosome: Chromosome<G>) -> Self;
     fn fitness(&self) -> f32;
-    fn chromosome(&self) -> &Chromosome;
+    fn chromosome(&self) -> &Chromosome<G>;
 }
 
 // ---------------------------------------------------------------------------- //
 
 /// SelectionMethod describes types used in selection stage of the genetic algorithm.
-pub trait SelectionMethod {
-    fn select<'a, I>(&self, rng: &mut dyn RngCore, population: &'a [I]) -> &'a I
+pub trait SelectionMethod<G = f32> {
+    /// Returns the weight of every individual, computed once per generation before selecting
+    /// any parent. Defaults to the fitness.
+    fn weights<I>(&self, population: &[I]) -> Vec<f32>
     where
-        I: Individual;
+        I: Individual<G>,
+    {
+        population
+            .iter()
+            .map(|individual| individual.fitness())
+            .collect()
+    }
+
+    /// Selects an individual, given the weights returned by [`SelectionMethod::weights`] for the
+    /// same population.
+    fn select<'a, I>(&self, rng: &mut dyn RngCore, population: &'a [I], weights: &[f32]) -> &'a I
+    where
+        I: Individual<G>;
 }
 
 /// RouletteWheelSelection represents selection based on portion on circular wheel with fixed point.
+#[derive(Clone, Copy, Debug)]
 pub struct RouletteWheelSelection;
 
-impl SelectionMethod for RouletteWheelSelection {
-    fn select<'a, I>(&self, rng: &mut dyn RngCore, population: &'a [I]) -> &'a I
+impl<G> SelectionMethod<G> for RouletteWheelSelection {
+    fn select<'a, I>(&self, rng: &mut dyn RngCore, population: &'a [I], weights: &[f32]) -> &'a I
     where
-        I: Individual,
+        I: Individual<G>,
     {
-        population
-            .choose_weighted(rng, |indiv| indiv.fitness())
-            .expect("got individual that doesn't exists")
+        roulette(rng, population, weights)
     }
 }
 
+// Spins the wheel over the given weights, each individual getting a portion of it proportional
+// to its weight. A wheel without any weight, e.g. when every fitness is clamped to zero, favours
+// no one so every individual gets the same portion.
+pub(crate) fn roulette<'a, I>(
+    rng: &mut dyn RngCore,
+    population: &'a [I],
+    weights: &[f32],
+) -> &'a I {
+    assert_eq!(population.len(), weights.len());
+
+    let index = match WeightedIndex::new(weights) {
+        Ok(index) => index.sample(rng),
+        Err(WeightedError::AllWeightsZero) => rng.gen_range(0..population.len()),
+        Err(_) => panic!("got individual that doesn't exists"),
+    };
+    &population[index]
+}
+
 /// CrossoverMethod describes types used in crossover stage of the genetic algorithm.
-pub trait CrossoverMethod {
+pub trait CrossoverMethod<G = f32> {
     fn crossover(
         &self,
         rng: &mut dyn RngCore,
-        parent_a: &Chromosome,
-        parent_b: &Chromosome,
-    ) -> Chromosome;
+        parent_a: &Chromosome<G>,
+        parent_b: &Chromosome<G>,
+    ) -> Chromosome<G>;
 }
 
 /// UniformCrossover represents crossover that returns new chromosome by selecting from both
 /// parent with equal chance.
 pub struct UniformCrossover;
 
-impl CrossoverMethod for UniformCrossover {
+impl<G> CrossoverMethod<G> for UniformCrossover
+where
+    G: Clone,
+{
     fn crossover(
         &self,
         rng: &mut dyn RngCore,
-        parent_a: &Chromosome,
-        parent_b: &Chromosome,
-    ) -> Chromosome {
+        parent_a: &Chromosome<G>,
+        parent_b: &Chromosome<G>,
+    ) -> Chromosome<G> {
         assert_eq!(parent_a.len(), parent_b.len());
 
         parent_a
             .iter()
             .zip(parent_b.iter())
-            .map(|(&a, &b)| if rng.gen_bool(0.5) { a } else { b })
+            .map(|(a, b)| if rng.gen_bool(0.5) { a } else { b }.clone())
             .collect()
     }
 }
 
 /// MutationMethod describes types used in mutation stage of the genetic algorithm.
-pub trait MutationMethod {
-    fn mutate(&self, rng: &mut dyn RngCore, child: &mut Chromosome);
+pub trait MutationMethod<G = f32> {
+    fn mutate(&self, rng: &mut dyn RngCore, child: &mut Chromosome<G>);
 }
 
 /// GaussianMutation represents mutation with probability in modifying genes with magnitude.
@@ -138,7 +257,7 @@ pub struct GaussianMutation {
 
 impl GaussianMutation {
     pub fn new(chance: f32, magnitude: f32) -> Self {
-        assert!(chance >= 0.0 && chance <= 1.0);
+        assert!((0.0..=1.0).contains(&chance));
         Self { chance, magnitude }
     }
 }
@@ -157,67 +276,138 @@ impl MutationMethod for GaussianMutation {
 // ---------------------------------------------------------------------------- //
 
 /// GaussianMutation represents wrapper for all evolutionary components as higher interface.
-pub struct GeneticAlgorithm<S> {
+pub struct GeneticAlgorithm<S, G = f32> {
     selection_method: S,
-    crossover_method: Box<dyn CrossoverMethod>,
-    mutation_method: Box<dyn MutationMethod>,
+    crossover_method: Box<dyn CrossoverMethod<G>>,
+    mutation_method: Box<dyn MutationMethod<G>>,
+    speciation: Option<Speciation>,
+    observers: Vec<Box<dyn Observer<G>>>,
+    best_fitness: Option<f32>, // Fittest individual met so far, for observers
 }
 
-impl<S> GeneticAlgorithm<S>
+impl<S, G> GeneticAlgorithm<S, G>
 where
-    S: SelectionMethod,
+    S: SelectionMethod<G>,
+    G: Gene,
 {
     /// Creates a new genetic algorithm with specified evolutionary components.
     pub fn new(
         selection_method: S,
-        crossover_method: impl CrossoverMethod + 'static,
-        mutation_method: impl MutationMethod + 'static,
+        crossover_method: impl CrossoverMethod<G> + 'static,
+        mutation_method: impl MutationMethod<G> + 'static,
     ) -> Self {
         Self {
             selection_method,
             crossover_method: Box::new(crossover_method),
             mutation_method: Box::new(mutation_method),
+            speciation: None,
+            observers: Vec::new(),
+            best_fitness: None,
         }
     }
 
+    /// Reports the species of every evaluated population in its statistics.
+    pub fn with_speciation(mut self, speciation: Speciation) -> Self {
+        self.speciation = Some(speciation);
+        self
+    }
+
+    /// Registers an observer notified after every evolved generation.
+    pub fn with_observer(mut self, observer: impl Observer<G> + 'static) -> Self {
+        self.observers.push(Box::new(observer));
+        self
+    }
+
     /// Takes initial population and retuns a genetically improved population for the next step in
     /// evolution.
-    pub fn evolve<I>(&self, rng: &mut dyn RngCore, population: &[I]) -> (Vec<I>, Statistics)
+    ///
+    /// Borrowing the algorithm mutably for the sake of its observers, none of them can reach back
+    /// into it while being notified.
+    pub fn evolve<I>(&mut self, rng: &mut dyn RngCore, population: &[I]) -> (Vec<I>, Statistics)
     where
-        I: Individual,
+        I: Individual<G>,
+    {
+        let scores: Vec<f32> = population.iter().map(|i| i.fitness()).collect();
+        self.evolve_with_scores(rng, population, &scores)
+    }
+
+    /// Same as [`GeneticAlgorithm::evolve`], but selecting parents on given scores (such as
+    /// novelty) instead of the individuals' fitness. Statistics still report the fitness.
+    pub fn evolve_with_scores<I>(
+        &mut self,
+        rng: &mut dyn RngCore,
+        population: &[I],
+        scores: &[f32],
+    ) -> (Vec<I>, Statistics)
+    where
+        I: Individual<G>,
     {
         assert!(!population.is_empty());
+        assert_eq!(population.len(), scores.len());
+
+        let scored: Vec<Scored<I>> = population
+            .iter()
+            .zip(scores)
+            .map(|(individual, &score)| Scored { individual, score })
+            .collect();
 
+        let weights = self.selection_method.weights(&scored);
         let new_population = (0..population.len())
             .map(|_| {
-                let parent_a = self.selection_method.select(rng, population).chromosome();
-                let parent_b = self.selection_method.select(rng, population).chromosome();
+                let parent_a = self.selection_method.select(rng, &scored, &weights);
+                let parent_b = self.selection_method.select(rng, &scored, &weights);
+                let (parent_a, parent_b) = (parent_a.chromosome(), parent_b.chromosome());
                 let mut child = self.crossover_method.crossover(rng, parent_a, parent_b);
                 self.mutation_method.mutate(rng, &mut child);
                 I::create(child)
             })
             .collect();
 
-        let stats = Statistics::new(population);
+        let mut stats = Statistics::new(population);
+        if let Some(speciation) = &self.speciation {
+            stats.species_sizes = speciation.sizes(population);
+        }
+        self.notify(population, &stats);
         (new_population, stats)
     }
+
+    fn notify<I>(&mut self, population: &[I], stats: &Statistics)
+    where
+        I: Individual<G>,
+    {
+        if self
+            .best_fitness
+            .is_none_or(|best_fitness| stats.max_fitness > best_fitness)
+        {
+            self.best_fitness = Some(stats.max_fitness);
+            let best = population[stats.best_index].chromosome();
+            for observer in &mut self.observers {
+                observer.on_new_best(best, stats.max_fitness);
+            }
+        }
+        for observer in &mut self.observers {
+            observer.on_generation(stats);
+        }
+    }
 }
 
 // ---------------------------------------------------------------------------- //
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct Statistics {
     pub min_fitness: f32,
     pub max_fitness: f32,
     pub avg_fitness: f32,
     pub fitness_std: f32,
     pub best_index: usize,
+    pub species_sizes: Vec<usize>, // Only reported when speciation is tracked
 }
 
 impl Statistics {
-    fn new<I>(population: &[I]) -> Self
+    /// Computes the fitness statistics of an evaluated population.
+    pub fn new<I, G>(population: &[I]) -> Self
     where
-        I: Individual,
+        I: Individual<G>,
     {
         assert!(!population.is_empty());
 
@@ -249,8 +439,46 @@ impl Statistics {
             best_index,
             fitness_std: (fitness_std / (population.len() as f32)).sqrt(),
             avg_fitness: sum_fitness / (population.len() as f32),
+            species_sizes: Vec::new(),
         }
     }
+
+    /// Returns the number of species, zero when speciation isn't tracked.
+    pub fn species_count(&self) -> usize {
+        self.species_sizes.len()
+    }
+}
+
+/// Returns the mean distance between every pair of chromosomes in the population, falling
+/// towards zero as the population converges.
+pub fn diversity<I, G>(population: &[I]) -> f32
+where
+    I: Individual<G>,
+    G: Gene,
+{
+    let pairs = population.len() * population.len().saturating_sub(1) / 2;
+    if pairs == 0 {
+        return 0.0;
+    }
+
+    let total: f32 = population
+        .iter()
+        .enumerate()
+        .flat_map(|(i, a)| {
+            population[i + 1..]
+                .iter()
+                .map(move |b| a.chromosome().distance(b.chromosome()))
+        })
+        .sum();
+    total / pairs as f32
+}
+
+/// Samples the standard normal distribution with the Box-Muller transform, as rand doesn't ship
+/// a normal distribution without rand_distr.
+pub fn standard_normal(rng: &mut dyn RngCore) -> f32 {
+    let u1: f32 = rng.gen_range(f32::EPSILON..1.0);
+    let u2: f32 = rng.r#gen();
+    (-2.0 * u1.ln()).sqrt() * (2.0 * std::f32::consts::PI * u2).cos()
 }
 
 // ---------------------------------------------------------------------------- //
@@ -260,8 +488,10 @@ mod test {
     use super::*;
     use rand::SeedableRng;
     use rand_chacha::ChaCha8Rng;
+    use std::cell::RefCell;
     use std::collections::BTreeMap;
     use std::iter::FromIterator;
+    use std::rc::Rc;
 
     mod gaussian_mutation {
         use super::*;
@@ -389,7 +619,7 @@ mod test {
     }
 
     impl Individual for TestFitness {
-        fn create(chromosome: Chromosome) -> Self {
+        fn create(_chromosome: Chromosome) -> Self {
             todo!()
         }
         fn fitness(&self) -> f32 {
@@ -410,9 +640,10 @@ mod test {
             TestFitness::new(3.0),
         ];
         let mut actual_histogram = BTreeMap::new();
+        let weights = RouletteWheelSelection.weights(&population);
         for _ in 0..1000 {
             let actual = RouletteWheelSelection
-                .select(&mut rng, &population)
+                .select(&mut rng, &population, &weights)
                 .fitness() as i32;
             *actual_histogram.entry(actual).or_insert(0) += 1;
         }
@@ -428,9 +659,27 @@ mod test {
         assert_eq!(actual_histogram, expected_histogram);
     }
 
+    #[test]
+    fn roulette_without_fitness() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let population: Vec<_> = (0..4).map(|_| TestFitness::new(0.0)).collect();
+        let weights = RouletteWheelSelection.weights(&population);
+
+        let mut chosen = [0; 4];
+        for _ in 0..1000 {
+            let actual = RouletteWheelSelection.select(&mut rng, &population, &weights);
+            chosen[population
+                .iter()
+                .position(|individual| std::ptr::eq(individual, actual))
+                .unwrap()] += 1;
+        }
+
+        assert!(chosen.iter().all(|&count| count > 200));
+    }
+
     mod genetic_algorithm_evolve {
         use super::*;
-        use approx::{assert_relative_eq, relative_eq};
+        use approx::relative_eq;
 
         impl PartialEq for Chromosome {
             fn eq(&self, other: &Self) -> bool {
@@ -439,37 +688,20 @@ mod test {
         }
 
         #[derive(Debug, Clone, PartialEq)]
-        enum TestIndividual {
-            WithChromosome { chromosome: Chromosome },
-            WithFitness { fitness: f32 },
-        }
-
-        impl TestIndividual {
-            fn new(fitness: f32) -> Self {
-                Self::WithFitness { fitness }
-            }
+        struct TestIndividual {
+            chromosome: Chromosome,
         }
 
         impl Individual for TestIndividual {
             fn create(chromosome: Chromosome) -> Self {
-                Self::WithChromosome { chromosome }
+                Self { chromosome }
             }
             fn chromosome(&self) -> &Chromosome {
-                match self {
-                    Self::WithChromosome { chromosome } => chromosome,
-                    Self::WithFitness { .. } => {
-                        panic!(
-                            "convertion of fitness to chromosome is not supported; TestIndividual::WithFitness"
-                        )
-                    }
-                }
+                &self.chromosome
             }
             fn fitness(&self) -> f32 {
-                match self {
-                    // Sums all chromosomes together as the fitness function
-                    Self::WithChromosome { chromosome } => chromosome.iter().sum(),
-                    Self::WithFitness { fitness } => *fitness,
-                }
+                // Sums all chromosomes together as the fitness function
+                self.chromosome.iter().sum()
             }
         }
 
@@ -483,7 +715,7 @@ mod test {
         fn genetic_algorithm() {
             let steps: i32 = 10;
             let mut rng = ChaCha8Rng::from_seed(Default::default());
-            let ga = GeneticAlgorithm::new(
+            let mut ga = GeneticAlgorithm::new(
                 RouletteWheelSelection,
                 UniformCrossover,
                 GaussianMutation::new(0.5, 0.5),
@@ -494,22 +726,76 @@ mod test {
                 individual(&[1.0, 2.0, 1.0]),
                 individual(&[1.0, 2.0, 4.0]),
             ];
-            let mut stats = Statistics::new(&population);
-
             for _ in 0..steps {
-                (population, stats) = ga.evolve(&mut rng, &population);
+                (population, _) = ga.evolve(&mut rng, &population);
             }
 
             let expected_population = vec![
-                individual(&[0.44769490, 2.0648358, 4.3058133]),
-                individual(&[1.21268670, 1.5538777, 2.8869110]),
-                individual(&[1.06176780, 2.2657390, 4.4287640]),
-                individual(&[0.95909685, 2.4618788, 4.0247330]),
+                individual(&[0.4476949, 2.0648358, 4.3058133]),
+                individual(&[1.2126867, 1.5538777, 2.886911]),
+                individual(&[1.0617678, 2.265739, 4.428764]),
+                individual(&[0.95909685, 2.4618788, 4.024733]),
             ];
 
             dbg!(&population);
 
             assert_eq!(population, expected_population);
         }
+
+        #[derive(Default)]
+        struct Recorder {
+            generations: Vec<f32>,
+            bests: Vec<f32>,
+        }
+
+        impl Observer for Recorder {
+            fn on_generation(&mut self, statistics: &Statistics) {
+                self.generations.push(statistics.max_fitness);
+            }
+            fn on_new_best(&mut self, chromosome: &Chromosome, fitness: f32) {
+                assert_eq!(chromosome.iter().sum::<f32>(), fitness);
+                self.bests.push(fitness);
+            }
+        }
+
+        #[test]
+        fn observers() {
+            let mut rng = ChaCha8Rng::from_seed(Default::default());
+            let recorder = Rc::new(RefCell::new(Recorder::default()));
+            let mut ga = GeneticAlgorithm::new(
+                RouletteWheelSelection,
+                UniformCrossover,
+                GaussianMutation::new(0.5, 0.5),
+            )
+            .with_observer(recorder.clone());
+            let mut population = vec![individual(&[1.0, 1.0]), individual(&[1.0, 2.0])];
+            for _ in 0..10 {
+                (population, _) = ga.evolve(&mut rng, &population);
+            }
+
+            let recorder = recorder.borrow();
+            assert_eq!(recorder.generations.len(), 10);
+            assert_eq!(recorder.bests[0], 3.0);
+            // Only strict improvements over the fittest met so far are reported.
+            assert!(recorder.bests.windows(2).all(|pair| pair[0] < pair[1]));
+            assert_eq!(
+                recorder.bests.last(),
+                recorder.generations.iter().max_by(|a, b| a.total_cmp(b))
+            );
+        }
+
+        #[test]
+        fn mean_pairwise_diversity() {
+            let converged = vec![individual(&[1.0, 2.0]), individual(&[1.0, 2.0])];
+            let spread = vec![
+                individual(&[0.0, 0.0]),
+                individual(&[3.0, 4.0]),
+                individual(&[0.0, 0.0]),
+            ];
+
+            assert_eq!(diversity(&converged), 0.0);
+            assert_eq!(diversity(&spread), 10.0 / 3.0);
+            assert_eq!(diversity(&converged[..1]), 0.0);
+        }
     }
 }
@@ -0,0 +1,246 @@
+use crate::*;
+
+/// Dimension describes an axis of the MAP-Elites grid, binning a behaviour feature over a range.
+/// Features outside of the range fall into the outer bins.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Dimension {
+    pub min: f32,
+    pub max: f32,
+    pub bins: usize,
+}
+
+impl Dimension {
+    pub fn new(min: f32, max: f32, bins: usize) -> Self {
+        assert!(min < max);
+        assert!(bins > 0);
+        Self { min, max, bins }
+    }
+
+    fn bin(&self, feature: f32) -> usize {
+        let ratio = (feature - self.min) / (self.max - self.min);
+        ((ratio * self.bins as f32).max(0.0) as usize).min(self.bins - 1)
+    }
+}
+
+/// Elite represents the best individual found so far in a cell of the grid.
+#[derive(Clone, Debug)]
+pub struct Elite {
+    pub chromosome: Chromosome,
+    pub fitness: f32,
+    pub behaviour: BehaviourDescriptor,
+}
+
+/// MapElites represents a quality-diversity archive, keeping the fittest chromosome of every
+/// cell in a grid over user-chosen behaviour dimensions.
+///
+/// Instead of a single best solution, the filled grid shows the best fitness reachable by every
+/// kind of behaviour, and new generations are bred from randomly picked elites.
+#[derive(Clone, Debug)]
+pub struct MapElites {
+    dimensions: Vec<Dimension>, // One per feature of the behaviour descriptors, in order
+    cells: Vec<Option<Elite>>,  // Row-major, the last dimension varying the fastest
+}
+
+impl MapElites {
+    pub fn new(dimensions: Vec<Dimension>) -> Self {
+        assert!(!dimensions.is_empty());
+
+        let cells = dimensions.iter().map(|dimension| dimension.bins).product();
+        Self {
+            dimensions,
+            cells: vec![None; cells],
+        }
+    }
+
+    pub fn dimensions(&self) -> &[Dimension] {
+        &self.dimensions
+    }
+
+    /// Returns the coordinates of the cell a behaviour falls into.
+    pub fn coordinates(&self, behaviour: &BehaviourDescriptor) -> Vec<usize> {
+        assert_eq!(behaviour.features().len(), self.dimensions.len());
+
+        self.dimensions
+            .iter()
+            .zip(behaviour.features())
+            .map(|(dimension, &feature)| dimension.bin(feature))
+            .collect()
+    }
+
+    /// Returns the elite of the cell at given coordinates, if any was found.
+    pub fn get(&self, coordinates: &[usize]) -> Option<&Elite> {
+        self.index(coordinates)
+            .and_then(|index| self.cells[index].as_ref())
+    }
+
+    /// Inserts the chromosome if its cell is empty or holds a less fit elite. Returns whether
+    /// the archive was improved.
+    pub fn insert(
+        &mut self,
+        chromosome: &Chromosome,
+        fitness: f32,
+        behaviour: &BehaviourDescriptor,
+    ) -> bool {
+        let coordinates = self.coordinates(behaviour);
+        let index = self
+            .index(&coordinates)
+            .expect("got cell that doesn't exists");
+        let cell = &mut self.cells[index];
+
+        if cell.as_ref().is_some_and(|elite| elite.fitness >= fitness) {
+            return false;
+        }
+        *cell = Some(Elite {
+            chromosome: chromosome.clone(),
+            fitness,
+            behaviour: behaviour.clone(),
+        });
+        true
+    }
+
+    /// Inserts every individual of an evaluated population, described by its own behaviour.
+    /// Returns the number of improved cells.
+    pub fn insert_population<I>(&mut self, population: &[I]) -> usize
+    where
+        I: Behaviour,
+    {
+        population
+            .iter()
+            .filter(|individual| {
+                self.insert(
+                    individual.chromosome(),
+                    individual.fitness(),
+                    individual.behaviour(),
+                )
+            })
+            .count()
+    }
+
+    /// Returns the elites found so far, in cell order.
+    pub fn elites(&self) -> impl Iterator<Item = &Elite> {
+        self.cells.iter().flatten()
+    }
+
+    /// Returns the ratio of cells holding an elite.
+    pub fn coverage(&self) -> f32 {
+        self.elites().count() as f32 / self.cells.len() as f32
+    }
+
+    /// Returns the fitness of the elite of every cell in row-major order, `None` for cells
+    /// nothing has reached yet.
+    pub fn heatmap(&self) -> Vec<Option<f32>> {
+        self.cells
+            .iter()
+            .map(|cell| cell.as_ref().map(|elite| elite.fitness))
+            .collect()
+    }
+
+    /// Breeds offsprings from uniformly picked elites, crossing two of them over before mutating.
+    pub fn breed(
+        &self,
+        rng: &mut dyn RngCore,
+        crossover_method: &dyn CrossoverMethod,
+        mutation_method: &dyn MutationMethod,
+        count: usize,
+    ) -> Vec<Chromosome> {
+        let elites: Vec<&Elite> = self.elites().collect();
+        assert!(!elites.is_empty(), "got no elite to breed from");
+
+        (0..count)
+            .map(|_| {
+                let parent_a = &elites[rng.gen_range(0..elites.len())].chromosome;
+                let parent_b = &elites[rng.gen_range(0..elites.len())].chromosome;
+                let mut child = crossover_method.crossover(rng, parent_a, parent_b);
+                mutation_method.mutate(rng, &mut child);
+                child
+            })
+            .collect()
+    }
+
+    fn index(&self, coordinates: &[usize]) -> Option<usize> {
+        if coordinates.len() != self.dimensions.len() {
+            return None;
+        }
+
+        self.dimensions
+            .iter()
+            .zip(coordinates)
+            .try_fold(0, |index, (dimension, &coordinate)| {
+                (coordinate < dimension.bins).then_some(index * dimension.bins + coordinate)
+            })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    fn archive() -> MapElites {
+        MapElites::new(vec![
+            Dimension::new(0.0, 1.0, 4),
+            Dimension::new(0.0, 2.0, 2),
+        ])
+    }
+
+    fn chromosome(gene: f32) -> Chromosome {
+        vec![gene].into_iter().collect()
+    }
+
+    fn behaviour(features: &[f32]) -> BehaviourDescriptor {
+        features.iter().cloned().collect()
+    }
+
+    #[test]
+    fn coordinates() {
+        let archive = archive();
+
+        assert_eq!(archive.coordinates(&behaviour(&[0.3, 1.5])), vec![1, 1]);
+        assert_eq!(archive.coordinates(&behaviour(&[-1.0, 0.0])), vec![0, 0]);
+        assert_eq!(archive.coordinates(&behaviour(&[1.0, 9.0])), vec![3, 1]);
+    }
+
+    #[test]
+    fn insert_keeps_the_fittest() {
+        let mut archive = archive();
+
+        assert!(archive.insert(&chromosome(1.0), 1.0, &behaviour(&[0.3, 1.5])));
+        assert!(!archive.insert(&chromosome(2.0), 0.5, &behaviour(&[0.3, 1.5])));
+        assert!(archive.insert(&chromosome(3.0), 2.0, &behaviour(&[0.4, 1.9])));
+        assert!(archive.insert(&chromosome(4.0), 0.0, &behaviour(&[0.9, 0.1])));
+
+        assert_eq!(archive.get(&[1, 1]).unwrap().chromosome[0], 3.0);
+        assert_eq!(archive.get(&[3, 0]).unwrap().fitness, 0.0);
+        assert!(archive.get(&[0, 0]).is_none());
+        assert!(archive.get(&[4, 0]).is_none());
+        assert_eq!(archive.coverage(), 0.25);
+
+        let mut heatmap = vec![None; 8];
+        heatmap[3] = Some(2.0);
+        heatmap[6] = Some(0.0);
+        assert_eq!(archive.heatmap(), heatmap);
+    }
+
+    #[test]
+    fn breed() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut archive = archive();
+        archive.insert(&chromosome(1.0), 1.0, &behaviour(&[0.0, 0.0]));
+        archive.insert(&chromosome(2.0), 1.0, &behaviour(&[1.0, 2.0]));
+
+        let offsprings = archive.breed(
+            &mut rng,
+            &UniformCrossover,
+            &GaussianMutation::new(0.0, 0.0),
+            10,
+        );
+
+        assert_eq!(offsprings.len(), 10);
+        assert!(
+            offsprings
+                .iter()
+                .all(|child| child[0] == 1.0 || child[0] == 2.0)
+        );
+    }
+}
@@ -0,0 +1,265 @@
+use crate::*;
+use std::collections::VecDeque;
+
+/// BehaviourDescriptor represents what an individual did during its evaluation, as a point in a
+/// behaviour space where novelty is measured.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct BehaviourDescriptor {
+    features: Vec<f32>,
+}
+
+impl BehaviourDescriptor {
+    pub fn new(features: Vec<f32>) -> Self {
+        Self { features }
+    }
+
+    pub fn features(&self) -> &[f32] {
+        &self.features
+    }
+
+    /// Returns the euclidean distance between both behaviours.
+    pub fn distance(&self, other: &BehaviourDescriptor) -> f32 {
+        assert_eq!(self.features.len(), other.features.len());
+
+        self.features
+            .iter()
+            .zip(&other.features)
+            .map(|(a, b)| (a - b).powi(2))
+            .sum::<f32>()
+            .sqrt()
+    }
+}
+
+impl FromIterator<f32> for BehaviourDescriptor {
+    fn from_iter<T: IntoIterator<Item = f32>>(iter: T) -> Self {
+        Self {
+            features: iter.into_iter().collect(),
+        }
+    }
+}
+
+/// Behaviour describes individuals able to describe their behaviour, on top of their fitness.
+pub trait Behaviour: Individual {
+    fn behaviour(&self) -> &BehaviourDescriptor;
+}
+
+/// NoveltySearch represents the scoring of individuals by how different their behaviour is from
+/// their k-nearest neighbours, among the population and an archive of past novel behaviours.
+///
+/// Rewarding novelty instead of fitness alone keeps exploring when fitness is deceptive, such as
+/// when food is too sparse for random brains to ever find it.
+#[derive(Clone, Debug)]
+pub struct NoveltySearch {
+    archive: VecDeque<BehaviourDescriptor>,
+    neighbours: usize, // Number of nearest neighbours averaged into the novelty
+    archive_threshold: f32, // Minimum novelty for a behaviour to be archived
+    archive_capacity: usize, // Oldest behaviours are forgotten past the capacity
+    fitness_weight: f32, // Weight of fitness in the score by range [0, 1], 0 is pure novelty
+}
+
+impl NoveltySearch {
+    pub fn new(
+        neighbours: usize,
+        archive_threshold: f32,
+        archive_capacity: usize,
+        fitness_weight: f32,
+    ) -> Self {
+        assert!(neighbours > 0);
+        assert!((0.0..=1.0).contains(&fitness_weight));
+
+        Self {
+            archive: VecDeque::new(),
+            neighbours,
+            archive_threshold,
+            archive_capacity,
+            fitness_weight,
+        }
+    }
+
+    /// Returns the archived behaviours, oldest first.
+    pub fn archive(&self) -> impl Iterator<Item = &BehaviourDescriptor> {
+        self.archive.iter()
+    }
+
+    /// Returns the mean distance of given behaviour to its nearest neighbours, among the others
+    /// and the archive.
+    pub fn novelty<'a>(
+        &'a self,
+        behaviour: &BehaviourDescriptor,
+        others: impl IntoIterator<Item = &'a BehaviourDescriptor>,
+    ) -> f32 {
+        let mut distances: Vec<f32> = others
+            .into_iter()
+            .chain(&self.archive)
+            .map(|other| behaviour.distance(other))
+            .collect();
+        distances.sort_by(f32::total_cmp);
+
+        let nearest = &distances[..self.neighbours.min(distances.len())];
+        if nearest.is_empty() {
+            return 0.0;
+        }
+        nearest.iter().sum::<f32>() / nearest.len() as f32
+    }
+
+    /// Returns the combined novelty and fitness score of every individual, both normalized by
+    /// their maximum in the population, then archives the behaviours novel enough.
+    pub fn scores<I>(&mut self, population: &[I]) -> Vec<f32>
+    where
+        I: Behaviour,
+    {
+        let novelties: Vec<f32> = population
+            .iter()
+            .enumerate()
+            .map(|(idx, individual)| {
+                let others = population
+                    .iter()
+                    .enumerate()
+                    .filter(|(other_idx, _)| *other_idx != idx)
+                    .map(|(_, other)| other.behaviour());
+                self.novelty(individual.behaviour(), others)
+            })
+            .collect();
+
+        let max_novelty = novelties.iter().fold(f32::EPSILON, |max, &n| max.max(n));
+        let max_fitness = population.iter().fold(f32::EPSILON, |max, individual| {
+            max.max(individual.fitness())
+        });
+
+        let scores = population
+            .iter()
+            .zip(&novelties)
+            .map(|(individual, novelty)| {
+                self.fitness_weight * individual.fitness() / max_fitness
+                    + (1.0 - self.fitness_weight) * novelty / max_novelty
+            })
+            .collect();
+
+        for (individual, &novelty) in population.iter().zip(&novelties) {
+            if novelty > self.archive_threshold {
+                self.archive.push_back(individual.behaviour().clone());
+            }
+        }
+        while self.archive.len() > self.archive_capacity {
+            self.archive.pop_front();
+        }
+
+        scores
+    }
+}
+
+/// Scored wraps an individual to select it on a given score instead of its own fitness.
+pub(crate) struct Scored<'a, I> {
+    pub(crate) individual: &'a I,
+    pub(crate) score: f32,
+}
+
+impl<I, G> Individual<G> for Scored<'_, I>
+where
+    I: Individual<G>,
+{
+    fn create(_chromosome: Chromosome<G>) -> Self {
+        unreachable!("scored individuals are only borrowed for selection")
+    }
+    fn fitness(&self) -> f32 {
+        self.score
+    }
+    fn chromosome(&self) -> &Chromosome<G> {
+        self.individual.chromosome()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[derive(Debug)]
+    struct TestIndividual {
+        chromosome: Chromosome,
+        fitness: f32,
+        behaviour: BehaviourDescriptor,
+    }
+
+    impl Individual for TestIndividual {
+        fn create(chromosome: Chromosome) -> Self {
+            Self {
+                chromosome,
+                fitness: 0.0,
+                behaviour: BehaviourDescriptor::default(),
+            }
+        }
+        fn fitness(&self) -> f32 {
+            self.fitness
+        }
+        fn chromosome(&self) -> &Chromosome {
+            &self.chromosome
+        }
+    }
+
+    impl Behaviour for TestIndividual {
+        fn behaviour(&self) -> &BehaviourDescriptor {
+            &self.behaviour
+        }
+    }
+
+    fn individual(behaviour: &[f32], fitness: f32) -> TestIndividual {
+        TestIndividual {
+            chromosome: vec![fitness].into_iter().collect(),
+            fitness,
+            behaviour: behaviour.iter().cloned().collect(),
+        }
+    }
+
+    // A fit crowd near the origin and an unfit loner far away.
+    fn population() -> Vec<TestIndividual> {
+        vec![
+            individual(&[0.0, 0.0], 2.0),
+            individual(&[0.0, 0.1], 2.0),
+            individual(&[0.1, 0.0], 2.0),
+            individual(&[1.0, 1.0], 0.0),
+        ]
+    }
+
+    #[test]
+    fn novelty() {
+        let search = NoveltySearch::new(2, 1.0, 10, 0.0);
+        let behaviour = BehaviourDescriptor::new(vec![0.0, 0.0]);
+        let others = [
+            BehaviourDescriptor::new(vec![0.0, 1.0]),
+            BehaviourDescriptor::new(vec![3.0, 0.0]),
+            BehaviourDescriptor::new(vec![0.0, 2.0]),
+        ];
+
+        assert_eq!(search.novelty(&behaviour, &others), 1.5);
+        assert_eq!(search.novelty(&behaviour, &[]), 0.0);
+    }
+
+    #[test]
+    fn pure_novelty_favours_the_loner() {
+        let mut search = NoveltySearch::new(2, 0.5, 10, 0.0);
+        let scores = search.scores(&population());
+
+        assert_eq!(scores[3], 1.0);
+        assert!(scores[..3].iter().all(|&score| score < 0.2));
+
+        // Only the loner was novel enough to be archived.
+        let archive: Vec<_> = search.archive().collect();
+        assert_eq!(archive, vec![&BehaviourDescriptor::new(vec![1.0, 1.0])]);
+    }
+
+    #[test]
+    fn pure_fitness_ignores_behaviour() {
+        let mut search = NoveltySearch::new(2, 0.5, 10, 1.0);
+        let scores = search.scores(&population());
+
+        assert_eq!(scores, vec![1.0, 1.0, 1.0, 0.0]);
+    }
+
+    #[test]
+    fn archive_capacity() {
+        let mut search = NoveltySearch::new(1, 0.0, 2, 0.0);
+        search.scores(&population());
+
+        assert_eq!(search.archive().count(), 2);
+    }
+}
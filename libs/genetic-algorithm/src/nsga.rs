@@ -0,0 +1,281 @@
+use crate::*;
+
+/// MultiObjective describes individuals evaluated on several objectives, all to be maximized.
+/// Objectives to be minimized, such as energy spent, are expected to be negated.
+pub trait MultiObjective: Individual {
+    fn objectives(&self) -> &[f32];
+}
+
+/// ParetoPoint represents a chromosome on the Pareto front, along with its objectives.
+#[derive(Clone, Debug)]
+pub struct ParetoPoint {
+    pub chromosome: Chromosome,
+    pub objectives: Vec<f32>,
+}
+
+/// Returns true if `a` is at least as good as `b` on every objective, and better on one.
+pub fn dominates(a: &[f32], b: &[f32]) -> bool {
+    assert_eq!(a.len(), b.len());
+
+    a.iter().zip(b).all(|(a, b)| a >= b) && a.iter().zip(b).any(|(a, b)| a > b)
+}
+
+/// Sorts objective vectors into successive non-dominated fronts, returning their indices. The
+/// first front is the Pareto front, the second one is only dominated by the first, and so on.
+pub fn non_dominated_sort(objectives: &[Vec<f32>]) -> Vec<Vec<usize>> {
+    let len = objectives.len();
+    let mut dominated: Vec<Vec<usize>> = vec![Vec::new(); len]; // Indices each one dominates
+    let mut domination_count = vec![0; len]; // Number of indices dominating each one
+
+    for a in 0..len {
+        for b in (a + 1)..len {
+            if dominates(&objectives[a], &objectives[b]) {
+                dominated[a].push(b);
+                domination_count[b] += 1;
+            } else if dominates(&objectives[b], &objectives[a]) {
+                dominated[b].push(a);
+                domination_count[a] += 1;
+            }
+        }
+    }
+
+    let mut fronts = Vec::new();
+    let mut front: Vec<usize> = (0..len).filter(|&i| domination_count[i] == 0).collect();
+    while !front.is_empty() {
+        let mut next = Vec::new();
+        for &i in &front {
+            for &j in &dominated[i] {
+                domination_count[j] -= 1;
+                if domination_count[j] == 0 {
+                    next.push(j);
+                }
+            }
+        }
+        fronts.push(front);
+        front = next;
+    }
+    fronts
+}
+
+/// Returns the crowding distance of every index of a front, in the same order. Boundaries of
+/// each objective get an infinite distance, so that the extremes of the front are kept.
+pub fn crowding_distance(objectives: &[Vec<f32>], front: &[usize]) -> Vec<f32> {
+    let mut distances = vec![0.0; front.len()];
+    let points: Vec<&[f32]> = front.iter().map(|&i| objectives[i].as_slice()).collect();
+    let dimensions = points.first().map_or(0, |point| point.len());
+
+    for objective in 0..dimensions {
+        let values: Vec<f32> = points.iter().map(|point| point[objective]).collect();
+        let mut order: Vec<usize> = (0..front.len()).collect();
+        order.sort_by(|&a, &b| values[a].total_cmp(&values[b]));
+
+        let (lowest, highest) = (order[0], order[front.len() - 1]);
+        let range = values[highest] - values[lowest];
+        distances[lowest] = f32::INFINITY;
+        distances[highest] = f32::INFINITY;
+        if range <= 0.0 {
+            continue;
+        }
+        for window in order.windows(3) {
+            distances[window[1]] += (values[window[2]] - values[window[0]]) / range;
+        }
+    }
+    distances
+}
+
+// Survivor of the previous generations, ranked by the crowded comparison.
+#[derive(Clone, Debug)]
+struct Ranked {
+    chromosome: Chromosome,
+    objectives: Vec<f32>,
+    rank: usize,
+    crowding: f32,
+}
+
+impl Ranked {
+    // Lower front first, then the least crowded.
+    fn beats(&self, other: &Ranked) -> bool {
+        self.rank < other.rank || (self.rank == other.rank && self.crowding > other.crowding)
+    }
+}
+
+/// Nsga2 represents the NSGA-II multi-objective evolutionary algorithm.
+///
+/// Each evaluated population is merged with the survivors of the previous generation, then the
+/// best half survives by non-dominated front and crowding distance. Offsprings are bred from
+/// survivors picked by binary tournaments.
+pub struct Nsga2 {
+    crossover_method: Box<dyn CrossoverMethod>,
+    mutation_method: Box<dyn MutationMethod>,
+    survivors: Vec<Ranked>,
+}
+
+impl Nsga2 {
+    pub fn new(
+        crossover_method: impl CrossoverMethod + 'static,
+        mutation_method: impl MutationMethod + 'static,
+    ) -> Self {
+        Self {
+            crossover_method: Box::new(crossover_method),
+            mutation_method: Box::new(mutation_method),
+            survivors: Vec::new(),
+        }
+    }
+
+    /// Takes an evaluated population and returns the offsprings for the next step in evolution,
+    /// along with the Pareto front among this population and the previous survivors.
+    pub fn evolve<I>(
+        &mut self,
+        rng: &mut dyn RngCore,
+        population: &[I],
+    ) -> (Vec<I>, Vec<ParetoPoint>)
+    where
+        I: MultiObjective,
+    {
+        assert!(!population.is_empty());
+
+        let pool: Vec<ParetoPoint> = self
+            .survivors
+            .drain(..)
+            .map(|ranked| ParetoPoint {
+                chromosome: ranked.chromosome,
+                objectives: ranked.objectives,
+            })
+            .chain(population.iter().map(|individual| ParetoPoint {
+                chromosome: individual.chromosome().clone(),
+                objectives: individual.objectives().to_vec(),
+            }))
+            .collect();
+        let objectives: Vec<Vec<f32>> = pool.iter().map(|point| point.objectives.clone()).collect();
+        let fronts = non_dominated_sort(&objectives);
+
+        // Filling the survivors front by front, the last one only partially by crowding.
+        for (rank, front) in fronts.iter().enumerate() {
+            let remaining = population.len() - self.survivors.len();
+            if remaining == 0 {
+                break;
+            }
+
+            let crowding = crowding_distance(&objectives, front);
+            let mut ranked: Vec<Ranked> = front
+                .iter()
+                .zip(crowding)
+                .map(|(&i, crowding)| Ranked {
+                    chromosome: pool[i].chromosome.clone(),
+                    objectives: pool[i].objectives.clone(),
+                    rank,
+                    crowding,
+                })
+                .collect();
+            ranked.sort_by(|a, b| b.crowding.total_cmp(&a.crowding));
+            ranked.truncate(remaining);
+            self.survivors.extend(ranked);
+        }
+
+        let new_population = (0..population.len())
+            .map(|_| {
+                let parent_a = &self.tournament(rng).chromosome;
+                let parent_b = &self.tournament(rng).chromosome;
+                let mut child = self.crossover_method.crossover(rng, parent_a, parent_b);
+                self.mutation_method.mutate(rng, &mut child);
+                I::create(child)
+            })
+            .collect();
+
+        let pareto_front = fronts[0].iter().map(|&i| pool[i].clone()).collect();
+        (new_population, pareto_front)
+    }
+
+    // Binary tournament on the crowded comparison.
+    fn tournament(&self, rng: &mut dyn RngCore) -> &Ranked {
+        let a = &self.survivors[rng.gen_range(0..self.survivors.len())];
+        let b = &self.survivors[rng.gen_range(0..self.survivors.len())];
+        if b.beats(a) { b } else { a }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    #[derive(Debug)]
+    struct TestIndividual {
+        chromosome: Chromosome,
+        objectives: Vec<f32>,
+    }
+
+    impl Individual for TestIndividual {
+        // Trading off both genes against each other, within a budget.
+        fn create(chromosome: Chromosome) -> Self {
+            let (a, b) = (chromosome[0], chromosome[1]);
+            let objectives = if a + b <= 1.0 {
+                vec![a, b]
+            } else {
+                vec![0.0, 0.0]
+            };
+            Self {
+                chromosome,
+                objectives,
+            }
+        }
+        fn fitness(&self) -> f32 {
+            self.objectives.iter().sum()
+        }
+        fn chromosome(&self) -> &Chromosome {
+            &self.chromosome
+        }
+    }
+
+    impl MultiObjective for TestIndividual {
+        fn objectives(&self) -> &[f32] {
+            &self.objectives
+        }
+    }
+
+    #[test]
+    fn domination() {
+        assert!(dominates(&[1.0, 1.0], &[0.0, 1.0]));
+        assert!(!dominates(&[1.0, 1.0], &[1.0, 1.0]));
+        assert!(!dominates(&[1.0, 0.0], &[0.0, 1.0]));
+    }
+
+    #[test]
+    fn sorting() {
+        let objectives = vec![
+            vec![1.0, 0.0],
+            vec![0.5, 0.5],
+            vec![0.0, 1.0],
+            vec![0.4, 0.4],
+            vec![0.0, 0.0],
+        ];
+
+        let fronts = non_dominated_sort(&objectives);
+        assert_eq!(fronts, vec![vec![0, 1, 2], vec![3], vec![4]]);
+
+        let crowding = crowding_distance(&objectives, &fronts[0]);
+        assert_eq!(crowding, vec![f32::INFINITY, 2.0, f32::INFINITY]);
+    }
+
+    #[test]
+    fn evolve() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut nsga = Nsga2::new(UniformCrossover, GaussianMutation::new(0.5, 0.1));
+        let mut population: Vec<TestIndividual> = (0..20)
+            .map(|_| TestIndividual::create(vec![0.1, 0.1].into_iter().collect()))
+            .collect();
+
+        let mut pareto_front = Vec::new();
+        for _ in 0..50 {
+            (population, pareto_front) = nsga.evolve(&mut rng, &population);
+        }
+
+        // The front spreads along the budget line, beyond the initial point.
+        assert!(pareto_front.len() > 1);
+        for point in &pareto_front {
+            let sum: f32 = point.objectives.iter().sum();
+            assert!(sum > 0.5, "got point far from the front: {point:?}");
+        }
+    }
+}
@@ -0,0 +1,26 @@
+use crate::*;
+use std::{cell::RefCell, rc::Rc};
+
+/// Observer describes types reacting to the evolution of populations, such as loggers,
+/// checkpoints or live plots. Every callback does nothing unless overridden.
+pub trait Observer<G = f32> {
+    /// Called after every evolved generation, with the statistics of the evaluated population.
+    fn on_generation(&mut self, _statistics: &Statistics) {}
+
+    /// Called when the evaluated population holds the fittest chromosome met so far.
+    fn on_new_best(&mut self, _chromosome: &Chromosome<G>, _fitness: f32) {}
+}
+
+// Shared observers, to be read back once registered.
+impl<G, O> Observer<G> for Rc<RefCell<O>>
+where
+    O: Observer<G>,
+{
+    fn on_generation(&mut self, statistics: &Statistics) {
+        self.borrow_mut().on_generation(statistics);
+    }
+
+    fn on_new_best(&mut self, chromosome: &Chromosome<G>, fitness: f32) {
+        self.borrow_mut().on_new_best(chromosome, fitness);
+    }
+}
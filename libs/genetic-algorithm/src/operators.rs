@@ -0,0 +1,341 @@
+use crate::*;
+use rand::distributions::uniform::SampleUniform;
+
+/// BitFlipMutation represents mutation of bit-strings, flipping every bit with a probability.
+pub struct BitFlipMutation {
+    chance: f32, // Probability of flipping a bit by range [0, 1]
+}
+
+impl BitFlipMutation {
+    pub fn new(chance: f32) -> Self {
+        assert!((0.0..=1.0).contains(&chance));
+        Self { chance }
+    }
+}
+
+impl MutationMethod<bool> for BitFlipMutation {
+    fn mutate(&self, rng: &mut dyn RngCore, child: &mut Chromosome<bool>) {
+        for gene in child.iter_mut() {
+            if rng.gen_bool(self.chance as f64) {
+                *gene = !*gene;
+            }
+        }
+    }
+}
+
+/// BoundedIntegerMutation represents mutation of integer genes, resetting every gene with a
+/// probability to a random value within inclusive bounds.
+pub struct BoundedIntegerMutation<G> {
+    chance: f32, // Probability of resetting a gene by range [0, 1]
+    min: G,
+    max: G,
+}
+
+impl<G> BoundedIntegerMutation<G>
+where
+    G: PartialOrd,
+{
+    pub fn new(chance: f32, min: G, max: G) -> Self {
+        assert!((0.0..=1.0).contains(&chance));
+        assert!(min <= max);
+        Self { chance, min, max }
+    }
+}
+
+impl<G> MutationMethod<G> for BoundedIntegerMutation<G>
+where
+    G: SampleUniform + PartialOrd + Copy,
+{
+    fn mutate(&self, rng: &mut dyn RngCore, child: &mut Chromosome<G>) {
+        for gene in child.iter_mut() {
+            if rng.gen_bool(self.chance as f64) {
+                *gene = rng.gen_range(self.min..=self.max);
+            }
+        }
+    }
+}
+
+/// SinglePointCrossover represents crossover that returns new chromosome by taking the genes of
+/// the first parent up to a random cut point, and the genes of the second parent after it.
+pub struct SinglePointCrossover;
+
+impl<G> CrossoverMethod<G> for SinglePointCrossover
+where
+    G: Clone,
+{
+    fn crossover(
+        &self,
+        rng: &mut dyn RngCore,
+        parent_a: &Chromosome<G>,
+        parent_b: &Chromosome<G>,
+    ) -> Chromosome<G> {
+        assert_eq!(parent_a.len(), parent_b.len());
+
+        let cut = rng.gen_range(0..=parent_a.len());
+        parent_a
+            .iter()
+            .take(cut)
+            .chain(parent_b.iter().skip(cut))
+            .cloned()
+            .collect()
+    }
+}
+
+/// OrderCrossover represents the order crossover (OX) of permutations, copying a random segment
+/// of the first parent and filling the remaining positions with the missing genes, in the order
+/// they appear in the second parent after the segment.
+pub struct OrderCrossover;
+
+impl<G> CrossoverMethod<G> for OrderCrossover
+where
+    G: Clone + PartialEq,
+{
+    fn crossover(
+        &self,
+        rng: &mut dyn RngCore,
+        parent_a: &Chromosome<G>,
+        parent_b: &Chromosome<G>,
+    ) -> Chromosome<G> {
+        assert_eq!(parent_a.len(), parent_b.len());
+
+        let len = parent_a.len();
+        let (start, end) = segment(rng, len);
+        let kept = &parent_a.genes()[start..end];
+
+        let mut filler = (0..len)
+            .map(|i| &parent_b[(end + i) % len])
+            .filter(|gene| !kept.contains(gene));
+        let mut genes: Vec<Option<G>> = vec![None; len];
+        for (offset, gene) in kept.iter().enumerate() {
+            genes[start + offset] = Some(gene.clone());
+        }
+        for i in 0..len - kept.len() {
+            genes[(end + i) % len] = filler.next().cloned();
+        }
+
+        genes
+            .into_iter()
+            .map(|gene| gene.expect("got parents that aren't permutations of each other"))
+            .collect()
+    }
+}
+
+/// PartiallyMappedCrossover represents the partially mapped crossover (PMX) of permutations,
+/// copying a random segment of the first parent and placing the displaced genes of the second
+/// parent through the mapping between both segments.
+pub struct PartiallyMappedCrossover;
+
+impl<G> CrossoverMethod<G> for PartiallyMappedCrossover
+where
+    G: Clone + PartialEq,
+{
+    fn crossover(
+        &self,
+        rng: &mut dyn RngCore,
+        parent_a: &Chromosome<G>,
+        parent_b: &Chromosome<G>,
+    ) -> Chromosome<G> {
+        assert_eq!(parent_a.len(), parent_b.len());
+
+        let (start, end) = segment(rng, parent_a.len());
+        let position_in_b = |gene: &G| {
+            parent_b
+                .iter()
+                .position(|other| other == gene)
+                .expect("got parents that aren't permutations of each other")
+        };
+
+        let mut child = parent_b.clone();
+        for i in start..end {
+            child[i] = parent_a[i].clone();
+        }
+        for i in start..end {
+            let gene = &parent_b[i];
+            if parent_a.genes()[start..end].contains(gene) {
+                continue;
+            }
+            // Following the mapping until landing outside of the segment.
+            let mut position = i;
+            while (start..end).contains(&position) {
+                position = position_in_b(&parent_a[position]);
+            }
+            child[position] = gene.clone();
+        }
+        child
+    }
+}
+
+/// SwapMutation represents mutation of permutations, swapping two random genes with a
+/// probability.
+pub struct SwapMutation {
+    chance: f32, // Probability of a swap by range [0, 1]
+}
+
+impl SwapMutation {
+    pub fn new(chance: f32) -> Self {
+        assert!((0.0..=1.0).contains(&chance));
+        Self { chance }
+    }
+}
+
+impl<G> MutationMethod<G> for SwapMutation {
+    fn mutate(&self, rng: &mut dyn RngCore, child: &mut Chromosome<G>) {
+        if child.is_empty() || !rng.gen_bool(self.chance as f64) {
+            return;
+        }
+        let a = rng.gen_range(0..child.len());
+        let b = rng.gen_range(0..child.len());
+        child.genes_mut().swap(a, b);
+    }
+}
+
+/// InversionMutation represents mutation of permutations, reversing a random segment with a
+/// probability.
+pub struct InversionMutation {
+    chance: f32, // Probability of an inversion by range [0, 1]
+}
+
+impl InversionMutation {
+    pub fn new(chance: f32) -> Self {
+        assert!((0.0..=1.0).contains(&chance));
+        Self { chance }
+    }
+}
+
+impl<G> MutationMethod<G> for InversionMutation {
+    fn mutate(&self, rng: &mut dyn RngCore, child: &mut Chromosome<G>) {
+        if !rng.gen_bool(self.chance as f64) {
+            return;
+        }
+        let (start, end) = segment(rng, child.len());
+        child.genes_mut()[start..end].reverse();
+    }
+}
+
+// Random half-open range of positions, possibly empty.
+fn segment(rng: &mut dyn RngCore, len: usize) -> (usize, usize) {
+    let a = rng.gen_range(0..=len);
+    let b = rng.gen_range(0..=len);
+    (a.min(b), a.max(b))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    fn permutation(genes: &[usize]) -> Chromosome<usize> {
+        genes.iter().copied().collect()
+    }
+
+    fn is_permutation(chromosome: &Chromosome<usize>) -> bool {
+        let mut genes = chromosome.genes().to_vec();
+        genes.sort();
+        genes.into_iter().eq(0..chromosome.len())
+    }
+
+    #[test]
+    fn bit_flip_mutation() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut child: Chromosome<bool> = vec![true, false, true].into_iter().collect();
+
+        BitFlipMutation::new(0.0).mutate(&mut rng, &mut child);
+        assert_eq!(child.genes(), &[true, false, true]);
+
+        BitFlipMutation::new(1.0).mutate(&mut rng, &mut child);
+        assert_eq!(child.genes(), &[false, true, false]);
+    }
+
+    #[test]
+    fn bounded_integer_mutation() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut child: Chromosome<i32> = vec![0; 100].into_iter().collect();
+
+        BoundedIntegerMutation::new(1.0, -3, 3).mutate(&mut rng, &mut child);
+        assert!(child.iter().all(|gene| (-3..=3).contains(gene)));
+        assert!(child.iter().any(|&gene| gene != 0));
+    }
+
+    #[test]
+    fn single_point_crossover() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let parent_a: Chromosome<i32> = vec![0; 10].into_iter().collect();
+        let parent_b: Chromosome<i32> = vec![1; 10].into_iter().collect();
+
+        let child = SinglePointCrossover.crossover(&mut rng, &parent_a, &parent_b);
+
+        // Genes of the first parent, then only genes of the second one.
+        let cut = child.iter().position(|&gene| gene == 1).unwrap_or(10);
+        assert!(child.iter().take(cut).all(|&gene| gene == 0));
+        assert!(child.iter().skip(cut).all(|&gene| gene == 1));
+    }
+
+    #[test]
+    fn permutation_crossovers() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let parent_a = permutation(&[0, 1, 2, 3, 4, 5, 6, 7, 8, 9]);
+        let parent_b = permutation(&[9, 3, 7, 8, 2, 6, 5, 1, 4, 0]);
+
+        for _ in 0..100 {
+            let child = OrderCrossover.crossover(&mut rng, &parent_a, &parent_b);
+            assert!(is_permutation(&child), "got invalid OX child: {child:?}");
+
+            let child = PartiallyMappedCrossover.crossover(&mut rng, &parent_a, &parent_b);
+            assert!(is_permutation(&child), "got invalid PMX child: {child:?}");
+        }
+    }
+
+    #[test]
+    fn permutation_mutations() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut child = permutation(&[0, 1, 2, 3, 4, 5, 6, 7, 8, 9]);
+
+        for _ in 0..100 {
+            SwapMutation::new(0.5).mutate(&mut rng, &mut child);
+            InversionMutation::new(0.5).mutate(&mut rng, &mut child);
+            assert!(is_permutation(&child), "got invalid mutation: {child:?}");
+        }
+        assert_ne!(
+            child.genes(),
+            permutation(&[0, 1, 2, 3, 4, 5, 6, 7, 8, 9]).genes()
+        );
+    }
+
+    #[derive(Debug)]
+    struct BitString {
+        chromosome: Chromosome<bool>,
+    }
+
+    impl Individual<bool> for BitString {
+        fn create(chromosome: Chromosome<bool>) -> Self {
+            Self { chromosome }
+        }
+        // Counting ones, off by one to keep every individual on the roulette wheel.
+        fn fitness(&self) -> f32 {
+            1.0 + self.chromosome.iter().filter(|&&bit| bit).count() as f32
+        }
+        fn chromosome(&self) -> &Chromosome<bool> {
+            &self.chromosome
+        }
+    }
+
+    #[test]
+    fn evolve_bit_strings() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut ga = GeneticAlgorithm::new(
+            RouletteWheelSelection,
+            UniformCrossover,
+            BitFlipMutation::new(0.01),
+        );
+        let mut population: Vec<BitString> = (0..20)
+            .map(|_| BitString::create(vec![false; 20].into_iter().collect()))
+            .collect();
+
+        let initial = Statistics::new(&population).avg_fitness;
+        for _ in 0..50 {
+            population = ga.evolve(&mut rng, &population).0;
+        }
+        assert!(Statistics::new(&population).avg_fitness > initial + 5.0);
+    }
+}
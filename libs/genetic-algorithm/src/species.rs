@@ -0,0 +1,255 @@
+use crate::*;
+
+/// Speciation represents the grouping of individuals into species, by comparing their
+/// chromosomes against the representative of every species met so far.
+#[derive(Clone, Copy, Debug)]
+pub struct Speciation {
+    threshold: f32, // Maximum distance to a species' representative to be part of it
+}
+
+impl Speciation {
+    pub fn new(threshold: f32) -> Self {
+        assert!(threshold > 0.0);
+        Self { threshold }
+    }
+
+    /// Returns the species index of every individual, where the first individual of each
+    /// species is its representative.
+    pub fn assign<I, G>(&self, population: &[I]) -> Vec<usize>
+    where
+        I: Individual<G>,
+        G: Gene,
+    {
+        let mut representatives: Vec<&Chromosome<G>> = Vec::new();
+
+        population
+            .iter()
+            .map(|individual| {
+                let chromosome = individual.chromosome();
+                let species = representatives.iter().position(|representative| {
+                    chromosome.distance(representative) < self.threshold
+                });
+
+                species.unwrap_or_else(|| {
+                    representatives.push(chromosome);
+                    representatives.len() - 1
+                })
+            })
+            .collect()
+    }
+
+    /// Returns the size of every species, in order of appearance.
+    pub fn sizes<I, G>(&self, population: &[I]) -> Vec<usize>
+    where
+        I: Individual<G>,
+        G: Gene,
+    {
+        Self::count(&self.assign(population))
+    }
+
+    /// Returns the size of every species, from the species index of every individual as given
+    /// by [`Speciation::assign`].
+    pub fn count(species: &[usize]) -> Vec<usize> {
+        let count = species.iter().max().map_or(0, |max| max + 1);
+
+        species.iter().fold(vec![0; count], |mut sizes, &species| {
+            sizes[species] += 1;
+            sizes
+        })
+    }
+}
+
+/// SpeciesSelection represents roulette wheel selection over explicitly shared fitness, where the
+/// fitness of an individual is divided by the size of its species.
+///
+/// A dominant species has to share its portion of the wheel, leaving room for smaller species
+/// exploring other behaviours.
+#[derive(Clone, Copy, Debug)]
+pub struct SpeciesSelection {
+    speciation: Speciation,
+}
+
+impl SpeciesSelection {
+    pub fn new(speciation: Speciation) -> Self {
+        Self { speciation }
+    }
+}
+
+impl<G> SelectionMethod<G> for SpeciesSelection
+where
+    G: Gene,
+{
+    fn weights<I>(&self, population: &[I]) -> Vec<f32>
+    where
+        I: Individual<G>,
+    {
+        let species = self.speciation.assign(population);
+        let sizes = Speciation::count(&species);
+
+        population
+            .iter()
+            .zip(&species)
+            .map(|(individual, &species)| individual.fitness() / sizes[species] as f32)
+            .collect()
+    }
+
+    fn select<'a, I>(&self, rng: &mut dyn RngCore, population: &'a [I], weights: &[f32]) -> &'a I
+    where
+        I: Individual<G>,
+    {
+        roulette(rng, population, weights)
+    }
+}
+
+/// FitnessSharing represents roulette wheel selection over fitness shared with neighbours, where
+/// the fitness of an individual is divided by its niche count.
+///
+/// Every individual closer than `sigma` contributes `1 - (distance / sigma)^alpha` to the niche
+/// count, including the individual itself.
+#[derive(Clone, Copy, Debug)]
+pub struct FitnessSharing {
+    sigma: f32, // Radius of a niche
+    alpha: f32, // Shape of the sharing function, linear at 1.0
+}
+
+impl FitnessSharing {
+    pub fn new(sigma: f32, alpha: f32) -> Self {
+        assert!(sigma > 0.0);
+        assert!(alpha > 0.0);
+        Self { sigma, alpha }
+    }
+
+    /// Returns the shared fitness of every individual.
+    pub fn shared_fitness<I, G>(&self, population: &[I]) -> Vec<f32>
+    where
+        I: Individual<G>,
+        G: Gene,
+    {
+        population
+            .iter()
+            .map(|individual| {
+                let niche_count: f32 = population
+                    .iter()
+                    .map(|other| {
+                        let distance = individual.chromosome().distance(other.chromosome());
+                        if distance < self.sigma {
+                            1.0 - (distance / self.sigma).powf(self.alpha)
+                        } else {
+                            0.0
+                        }
+                    })
+                    .sum();
+
+                individual.fitness() / niche_count
+            })
+            .collect()
+    }
+}
+
+impl<G> SelectionMethod<G> for FitnessSharing
+where
+    G: Gene,
+{
+    fn weights<I>(&self, population: &[I]) -> Vec<f32>
+    where
+        I: Individual<G>,
+    {
+        self.shared_fitness(population)
+    }
+
+    fn select<'a, I>(&self, rng: &mut dyn RngCore, population: &'a [I], weights: &[f32]) -> &'a I
+    where
+        I: Individual<G>,
+    {
+        roulette(rng, population, weights)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+    use std::collections::BTreeMap;
+
+    #[derive(Debug)]
+    struct TestIndividual {
+        chromosome: Chromosome,
+        fitness: f32,
+    }
+
+    impl Individual for TestIndividual {
+        fn create(chromosome: Chromosome) -> Self {
+            Self {
+                chromosome,
+                fitness: 0.0,
+            }
+        }
+        fn fitness(&self) -> f32 {
+            self.fitness
+        }
+        fn chromosome(&self) -> &Chromosome {
+            &self.chromosome
+        }
+    }
+
+    fn individual(genes: &[f32], fitness: f32) -> TestIndividual {
+        TestIndividual {
+            chromosome: genes.iter().cloned().collect(),
+            fitness,
+        }
+    }
+
+    // A crowded species of three near (0, 0) and a lonely one at (10, 10), equally fit.
+    fn population() -> Vec<TestIndividual> {
+        vec![
+            individual(&[0.0, 0.0], 1.0),
+            individual(&[0.5, 0.0], 1.0),
+            individual(&[0.0, 0.5], 1.0),
+            individual(&[10.0, 10.0], 1.0),
+        ]
+    }
+
+    #[test]
+    fn speciation() {
+        let speciation = Speciation::new(1.0);
+
+        assert_eq!(speciation.assign(&population()), vec![0, 0, 0, 1]);
+        assert_eq!(speciation.sizes(&population()), vec![3, 1]);
+        assert_eq!(Speciation::new(0.1).sizes(&population()), vec![1, 1, 1, 1]);
+        assert_eq!(Speciation::count(&[0, 1, 0, 2]), vec![2, 1, 1]);
+    }
+
+    #[test]
+    fn species_selection() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let population = population();
+        let selection = SpeciesSelection::new(Speciation::new(1.0));
+        let weights = selection.weights(&population);
+
+        let mut histogram = BTreeMap::new();
+        for _ in 0..1000 {
+            let selected = selection
+                .select(&mut rng, &population, &weights)
+                .chromosome()[0];
+            *histogram.entry(selected as i32).or_insert(0) += 1;
+        }
+
+        // Both species get about half of the wheel, despite their sizes.
+        let crowded = histogram[&0];
+        let lonely = histogram[&10];
+        assert_eq!(crowded + lonely, 1000);
+        assert!((450..=550).contains(&lonely), "lonely species got {lonely}");
+    }
+
+    #[test]
+    fn fitness_sharing() {
+        let sharing = FitnessSharing::new(1.0, 1.0);
+        let shared = sharing.shared_fitness(&population());
+
+        // The lonely individual only shares with itself.
+        assert_eq!(shared[3], 1.0);
+        assert!(shared[0] < shared[1]);
+        assert!(shared[1] < 1.0);
+    }
+}
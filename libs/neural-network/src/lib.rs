@@ -1,6 +1,11 @@
 use core::f32;
 use rand::{Rng, RngCore};
-use std::iter::once;
+use std::{fmt, iter::once};
+
+mod render;
+mod train;
+
+pub use self::{render::*, train::*};
 
 /*
 * # Neural network in Rust
@@ -9,11 +14,59 @@ use std::iter::once;
 *
 */
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct LayerTopology {
     pub neurons: usize,
 }
 
+/// Errors reported by the fallible constructors and propagation of [`Network`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NetworkError {
+    /// The topology needs at least an input and an output layer.
+    NotEnoughLayers { expected: usize, actual: usize },
+    /// The weights ran out before every neuron got its bias and weights.
+    NotEnoughWeights { expected: usize, actual: usize },
+    /// Some weights were left over after every neuron was built.
+    TooManyWeights { expected: usize, actual: usize },
+    /// The inputs don't match the size of the input layer.
+    InputSizeMismatch { expected: usize, actual: usize },
+    /// The training targets don't match the size of the output layer.
+    TargetSizeMismatch { expected: usize, actual: usize },
+}
+
+impl fmt::Display for NetworkError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotEnoughLayers { expected, actual } => {
+                write!(
+                    f,
+                    "got not enough layers! expected {expected}, got {actual}"
+                )
+            }
+            Self::NotEnoughWeights { expected, actual } => {
+                write!(
+                    f,
+                    "got not enough weights! expected {expected}, got {actual}"
+                )
+            }
+            Self::TooManyWeights { expected, actual } => {
+                write!(f, "got too much weights! expected {expected}, got {actual}")
+            }
+            Self::InputSizeMismatch { expected, actual } => {
+                write!(f, "got wrong input size! expected {expected}, got {actual}")
+            }
+            Self::TargetSizeMismatch { expected, actual } => {
+                write!(
+                    f,
+                    "got wrong target size! expected {expected}, got {actual}"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for NetworkError {}
+
 #[derive(Debug)]
 pub struct Network {
     layers: Vec<Layer>,
@@ -22,14 +75,56 @@ pub struct Network {
 impl Network {
     /// Creates a network initialized with random values.
     pub fn random(rng: &mut dyn RngCore, layers: &[LayerTopology]) -> Self {
-        assert!(layers.len() > 1);
+        Self::try_random(rng, layers).unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Creates a network initialized with random values, failing on topologies with less than
+    /// two layers.
+    pub fn try_random(
+        rng: &mut dyn RngCore,
+        layers: &[LayerTopology],
+    ) -> Result<Self, NetworkError> {
+        Self::check_topology(layers)?;
 
         let layers = layers
             .windows(2)
             .map(|adj_layer| Layer::random(rng, adj_layer[0].neurons, adj_layer[1].neurons))
             .collect::<Vec<Layer>>();
 
-        Self { layers }
+        Ok(Self { layers })
+    }
+
+    /// Returns the layers of neurons, excluding the input layer which holds no neurons.
+    pub fn layers(&self) -> &[Layer] {
+        &self.layers
+    }
+
+    /// Returns the topology the network was built from, including the input layer.
+    pub fn topology(&self) -> Vec<LayerTopology> {
+        let inputs = self.layers.first().map_or(0, Layer::input_size);
+        once(LayerTopology { neurons: inputs })
+            .chain(self.layers.iter().map(|layer| LayerTopology {
+                neurons: layer.neurons.len(),
+            }))
+            .collect()
+    }
+
+    /// Returns the number of weights (biases included) a network of given topology holds.
+    pub fn count_weights(topology: &[LayerTopology]) -> usize {
+        topology
+            .windows(2)
+            .map(|adj_layer| (adj_layer[0].neurons + 1) * adj_layer[1].neurons)
+            .sum()
+    }
+
+    fn check_topology(topology: &[LayerTopology]) -> Result<(), NetworkError> {
+        if topology.len() < 2 {
+            return Err(NetworkError::NotEnoughLayers {
+                expected: 2,
+                actual: topology.len(),
+            });
+        }
+        Ok(())
     }
 
     pub fn weights(&self) -> impl Iterator<Item = f32> {
@@ -44,6 +139,29 @@ impl Network {
         topology: &[LayerTopology],
         weights: impl IntoIterator<Item = f32>,
     ) -> Self {
+        Self::try_from_weights(topology, weights).unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Creates a network from its flattened weights, failing if the weight count doesn't match
+    /// the topology.
+    pub fn try_from_weights(
+        topology: &[LayerTopology],
+        weights: impl IntoIterator<Item = f32>,
+    ) -> Result<Self, NetworkError> {
+        Self::check_topology(topology)?;
+
+        // Counting the weights upfront so that errors can report both sides.
+        let weights: Vec<f32> = weights.into_iter().collect();
+        let expected = Self::count_weights(topology);
+        let actual = weights.len();
+
+        if actual < expected {
+            return Err(NetworkError::NotEnoughWeights { expected, actual });
+        }
+        if actual > expected {
+            return Err(NetworkError::TooManyWeights { expected, actual });
+        }
+
         // Making the iterators mutable while consuming it.
         let mut weights = weights.into_iter();
 
@@ -54,26 +172,70 @@ impl Network {
             })
             .collect();
 
-        if weights.next().is_some() {
-            panic!("got too much weights!");
+        Ok(Self { layers })
+    }
+
+    pub fn propagate(&self, inputs: Vec<f32>) -> Vec<f32> {
+        self.try_propagate(inputs)
+            .unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Propagates the inputs through every layer, failing if the inputs don't match the size of
+    /// the input layer.
+    ///
+    /// Hidden layers are ReLU, while the output layer is linear so that outputs can be negative.
+    pub fn try_propagate(&self, inputs: Vec<f32>) -> Result<Vec<f32>, NetworkError> {
+        (0..self.layers.len()).try_fold(inputs, |inputs, idx| self.try_propagate_layer(idx, inputs)) // Folding design pattern
+    }
+
+    /// Propagates the inputs like [`Network::propagate`], but returns the activations of every
+    /// layer. The first entry holds the inputs and the last one holds the outputs.
+    pub fn propagate_traced(&self, inputs: Vec<f32>) -> Vec<Vec<f32>> {
+        self.try_propagate_traced(inputs)
+            .unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Fallible version of [`Network::propagate_traced`].
+    pub fn try_propagate_traced(&self, inputs: Vec<f32>) -> Result<Vec<Vec<f32>>, NetworkError> {
+        let mut activations = Vec::with_capacity(self.layers.len() + 1);
+        activations.push(inputs);
+
+        for idx in 0..self.layers.len() {
+            let outputs = self.try_propagate_layer(idx, activations[idx].clone())?;
+            activations.push(outputs);
         }
 
-        Self { layers }
+        Ok(activations)
     }
 
-    pub fn propagate(&self, inputs: Vec<f32>) -> Vec<f32> {
-        self.layers
-            .iter()
-            .fold(inputs, |inputs, layer| layer.propagate(inputs)) // Folding design pattern
+    fn try_propagate_layer(&self, idx: usize, inputs: Vec<f32>) -> Result<Vec<f32>, NetworkError> {
+        if idx + 1 == self.layers.len() {
+            self.layers[idx].try_propagate_linear(inputs)
+        } else {
+            self.layers[idx].try_propagate(inputs)
+        }
     }
 }
 
+/// Layer is a read-only view of a fully connected layer of neurons.
 #[derive(Debug)]
-struct Layer {
+pub struct Layer {
     neurons: Vec<Neuron>,
 }
 
 impl Layer {
+    /// Returns the neurons of the layer.
+    pub fn neurons(&self) -> &[Neuron] {
+        &self.neurons
+    }
+
+    /// Returns the number of inputs every neuron of the layer takes.
+    pub fn input_size(&self) -> usize {
+        self.neurons
+            .first()
+            .map_or(0, |neuron| neuron.weights.len())
+    }
+
     // `|_|` toilet closure...
     fn random(rng: &mut dyn RngCore, input_size: usize, output_size: usize) -> Self {
         let neurons = (0..output_size)
@@ -94,21 +256,45 @@ impl Layer {
         Self { neurons }
     }
 
+    #[cfg(test)]
     fn propagate(&self, inputs: Vec<f32>) -> Vec<f32> {
+        self.try_propagate(inputs)
+            .unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    fn try_propagate(&self, inputs: Vec<f32>) -> Result<Vec<f32>, NetworkError> {
         self.neurons
             .iter()
-            .map(|neuron| neuron.propagate(&inputs))
+            .map(|neuron| neuron.try_propagate(&inputs))
+            .collect()
+    }
+
+    fn try_propagate_linear(&self, inputs: Vec<f32>) -> Result<Vec<f32>, NetworkError> {
+        self.neurons
+            .iter()
+            .map(|neuron| neuron.try_sum(&inputs))
             .collect()
     }
 }
 
+/// Neuron is a read-only view of a single neuron, ReLU unless it's in the output layer.
 #[derive(Debug)]
-struct Neuron {
+pub struct Neuron {
     weights: Vec<f32>,
     bias: f32,
 }
 
 impl Neuron {
+    /// Returns the weights of the incoming connections, in order of the previous layer.
+    pub fn weights(&self) -> &[f32] {
+        &self.weights
+    }
+
+    /// Returns the bias of the neuron.
+    pub fn bias(&self) -> f32 {
+        self.bias
+    }
+
     // rand::RngCore implements the rand::Rng trait that contains the gen_range() method
     // in comparison to rand_core::RngCore, which requires trait bounds for Rng trait.
     fn random(rng: &mut dyn RngCore, input_size: usize) -> Self {
@@ -126,8 +312,24 @@ impl Neuron {
         Self { bias, weights }
     }
 
+    #[cfg(test)]
     fn propagate(&self, inputs: &[f32]) -> f32 {
-        assert_eq!(inputs.len(), self.weights.len());
+        self.try_propagate(inputs)
+            .unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    fn try_propagate(&self, inputs: &[f32]) -> Result<f32, NetworkError> {
+        Ok(self.try_sum(inputs)?.max(0.0)) // Mimics ReLU activation in range [0, R)
+    }
+
+    // Weighted sum of the inputs plus the bias, before any activation.
+    fn try_sum(&self, inputs: &[f32]) -> Result<f32, NetworkError> {
+        if inputs.len() != self.weights.len() {
+            return Err(NetworkError::InputSizeMismatch {
+                expected: self.weights.len(),
+                actual: inputs.len(),
+            });
+        }
         // Instead of using bound checks in indexing, using combinators speeds up checks
         // `::<>` turbofish explicitize generic arguments
         let output = inputs
@@ -135,7 +337,7 @@ impl Neuron {
             .zip(&self.weights)
             .map(|(input, weight)| input * weight)
             .sum::<f32>();
-        (output + self.bias).max(0.0) // Mimics ReLU activation in range [0, R)
+        Ok(output + self.bias)
     }
 }
 
@@ -172,6 +374,30 @@ mod tests {
             );
         }
 
+        #[test]
+        fn propagate_linear_output() {
+            let network = Network {
+                layers: vec![
+                    Layer {
+                        neurons: vec![Neuron {
+                            bias: -1.0,
+                            weights: vec![1.0],
+                        }],
+                    },
+                    Layer {
+                        neurons: vec![Neuron {
+                            bias: -0.5,
+                            weights: vec![2.0],
+                        }],
+                    },
+                ],
+            };
+
+            // The hidden layer clamps at zero, the output layer doesn't.
+            assert_relative_eq!(network.propagate(vec![0.0]).as_slice(), [-0.5].as_ref());
+            assert_relative_eq!(network.propagate(vec![2.0]).as_slice(), [1.5].as_ref());
+        }
+
         #[test]
         fn weights() {
             let network = Network {
@@ -196,6 +422,120 @@ mod tests {
 
             assert_relative_eq!(actual.as_slice(), expected.as_slice());
         }
+
+        #[test]
+        fn propagate_traced() {
+            let mut rng = ChaCha8Rng::from_seed(Default::default());
+            let topology = vec![
+                LayerTopology { neurons: 2 },
+                LayerTopology { neurons: 3 },
+                LayerTopology { neurons: 1 },
+            ];
+            let network = Network::random(&mut rng, &topology);
+            let trace = network.propagate_traced(vec![1.0, 0.5]);
+
+            assert_eq!(trace.len(), 3);
+            assert_relative_eq!(trace[0].as_slice(), [1.0, 0.5].as_ref());
+            assert_eq!(trace[1].len(), 3);
+            assert_relative_eq!(
+                trace[2].as_slice(),
+                network.propagate(vec![1.0, 0.5]).as_slice()
+            );
+        }
+
+        #[test]
+        fn topology() {
+            let mut rng = ChaCha8Rng::from_seed(Default::default());
+            let topology = vec![
+                LayerTopology { neurons: 4 },
+                LayerTopology { neurons: 3 },
+                LayerTopology { neurons: 2 },
+            ];
+            let network = Network::random(&mut rng, &topology);
+
+            assert_eq!(network.topology(), topology);
+            assert_eq!(network.layers()[1].input_size(), 3);
+            assert_eq!(network.layers()[1].neurons()[0].weights().len(), 3);
+        }
+
+        #[test]
+        fn try_random() {
+            let mut rng = ChaCha8Rng::from_seed(Default::default());
+            let topology = vec![LayerTopology { neurons: 2 }];
+            let error = Network::try_random(&mut rng, &topology).unwrap_err();
+            assert_eq!(
+                error,
+                NetworkError::NotEnoughLayers {
+                    expected: 2,
+                    actual: 1
+                }
+            );
+        }
+
+        mod try_from_weights {
+            use super::*;
+
+            fn topology() -> Vec<LayerTopology> {
+                vec![LayerTopology { neurons: 3 }, LayerTopology { neurons: 2 }]
+            }
+
+            #[test]
+            fn given_exact_weights() {
+                let weights: Vec<f32> = (0..8).map(|w| w as f32 / 10.0).collect();
+                let network = Network::try_from_weights(&topology(), weights.clone()).unwrap();
+                let actual: Vec<_> = network.weights().collect();
+                assert_relative_eq!(actual.as_slice(), weights.as_slice());
+            }
+
+            #[test]
+            fn given_not_enough_weights() {
+                let error = Network::try_from_weights(&topology(), vec![0.0; 7]).unwrap_err();
+                assert_eq!(
+                    error,
+                    NetworkError::NotEnoughWeights {
+                        expected: 8,
+                        actual: 7
+                    }
+                );
+            }
+
+            #[test]
+            fn given_too_many_weights() {
+                let error = Network::try_from_weights(&topology(), vec![0.0; 10]).unwrap_err();
+                assert_eq!(
+                    error,
+                    NetworkError::TooManyWeights {
+                        expected: 8,
+                        actual: 10
+                    }
+                );
+                assert_eq!(
+                    error.to_string(),
+                    "got too much weights! expected 8, got 10"
+                );
+            }
+        }
+
+        #[test]
+        fn try_propagate() {
+            let mut rng = ChaCha8Rng::from_seed(Default::default());
+            let topology = vec![LayerTopology { neurons: 2 }, LayerTopology { neurons: 1 }];
+            let network = Network::random(&mut rng, &topology);
+            assert_eq!(
+                network.try_propagate(vec![1.0, 1.0, 1.0]),
+                Err(NetworkError::InputSizeMismatch {
+                    expected: 2,
+                    actual: 3
+                })
+            );
+        }
+
+        #[test]
+        #[should_panic(expected = "got not enough weights! expected 8, got 1")]
+        fn from_weights_panics() {
+            let topology = vec![LayerTopology { neurons: 3 }, LayerTopology { neurons: 2 }];
+            Network::from_weights(&topology, vec![0.0]);
+        }
     }
 
     mod layers {
@@ -250,5 +590,21 @@ mod tests {
                 (-0.3 * 0.5) + (0.8 * 1.0) + 0.5,
             );
         }
+
+        #[test]
+        fn try_propagate() {
+            let neuron = Neuron {
+                bias: 0.5,
+                weights: vec![-0.3, 0.8],
+            };
+
+            assert_eq!(
+                neuron.try_propagate(&[1.0]),
+                Err(NetworkError::InputSizeMismatch {
+                    expected: 2,
+                    actual: 1
+                })
+            );
+        }
     }
 }
@@ -0,0 +1,277 @@
+use crate::*;
+use std::fmt::Write;
+
+// Layout of the SVG renderer, in pixels.
+const SVG_LAYER_SPACING: f32 = 160.0;
+const SVG_NEURON_SPACING: f32 = 36.0;
+const SVG_NEURON_RADIUS: f32 = 10.0;
+const SVG_MARGIN: f32 = 80.0;
+const SVG_MAX_STROKE: f32 = 4.0;
+
+// Edge colours encoding the sign of a weight.
+const POSITIVE_COLOR: &str = "#2166ac";
+const NEGATIVE_COLOR: &str = "#b2182b";
+
+/// Labels names the input and output neurons of a rendered network.
+///
+/// Neurons without a label fall back to `in {i}` and `out {i}`.
+#[derive(Debug, Clone, Default)]
+pub struct Labels {
+    pub inputs: Vec<String>,
+    pub outputs: Vec<String>,
+}
+
+impl Labels {
+    pub fn new(inputs: Vec<String>, outputs: Vec<String>) -> Self {
+        Self { inputs, outputs }
+    }
+}
+
+impl Network {
+    /// Renders the network as a Graphviz DOT digraph, laid out from left to right.
+    ///
+    /// Edge colour encodes the sign of the weight (blue for excitatory, red for inhibitory),
+    /// while the pen width and opacity encode its magnitude relative to the largest weight.
+    pub fn to_dot(&self, labels: &Labels) -> String {
+        let topology = self.topology();
+        let max_weight = self.max_abs_weight();
+        let mut dot = String::new();
+
+        writeln!(dot, "digraph network {{").unwrap();
+        writeln!(dot, "    rankdir=LR;").unwrap();
+        writeln!(dot, "    splines=line;").unwrap();
+        writeln!(dot, "    node [shape=circle, fixedsize=true, width=0.6];").unwrap();
+
+        for (layer_idx, layer) in topology.iter().enumerate() {
+            writeln!(dot, "    subgraph cluster_{layer_idx} {{").unwrap();
+            writeln!(dot, "        color=none;").unwrap();
+            for neuron_idx in 0..layer.neurons {
+                let label = neuron_label(labels, &topology, layer_idx, neuron_idx);
+                writeln!(
+                    dot,
+                    "        n{layer_idx}_{neuron_idx} [label=\"{}\"];",
+                    escape_dot(&label)
+                )
+                .unwrap();
+            }
+            writeln!(dot, "    }}").unwrap();
+        }
+
+        for (layer_idx, layer) in self.layers.iter().enumerate() {
+            for (neuron_idx, neuron) in layer.neurons.iter().enumerate() {
+                for (input_idx, &weight) in neuron.weights.iter().enumerate() {
+                    let magnitude = weight.abs() / max_weight;
+                    writeln!(
+                        dot,
+                        "    n{layer_idx}_{input_idx} -> n{}_{neuron_idx} [penwidth={:.2}, color=\"{}{:02x}\"];",
+                        layer_idx + 1,
+                        0.25 + magnitude * (SVG_MAX_STROKE - 0.25),
+                        weight_color(weight),
+                        opacity(magnitude),
+                    )
+                    .unwrap();
+                }
+            }
+        }
+
+        writeln!(dot, "}}").unwrap();
+        dot
+    }
+
+    /// Renders the network as a self-contained SVG image, using the same weight encoding as
+    /// [`Network::to_dot`].
+    pub fn to_svg(&self, labels: &Labels) -> String {
+        let topology = self.topology();
+        let max_weight = self.max_abs_weight();
+        let max_neurons = topology
+            .iter()
+            .map(|layer| layer.neurons)
+            .max()
+            .unwrap_or(0);
+
+        let width = 2.0 * SVG_MARGIN + SVG_LAYER_SPACING * (topology.len() - 1) as f32;
+        let height = 2.0 * SVG_NEURON_RADIUS + SVG_NEURON_SPACING * max_neurons as f32;
+
+        // Centering every layer vertically against the widest layer.
+        let position = |layer_idx: usize, neuron_idx: usize| {
+            let offset = (max_neurons - topology[layer_idx].neurons) as f32 / 2.0;
+            (
+                SVG_MARGIN + SVG_LAYER_SPACING * layer_idx as f32,
+                SVG_NEURON_RADIUS + SVG_NEURON_SPACING * (offset + neuron_idx as f32 + 0.5),
+            )
+        };
+
+        let mut svg = String::new();
+        writeln!(
+            svg,
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"{height}\" viewBox=\"0 0 {width} {height}\">"
+        )
+        .unwrap();
+        writeln!(svg, "<rect width=\"100%\" height=\"100%\" fill=\"white\"/>").unwrap();
+
+        for (layer_idx, layer) in self.layers.iter().enumerate() {
+            for (neuron_idx, neuron) in layer.neurons.iter().enumerate() {
+                let (x2, y2) = position(layer_idx + 1, neuron_idx);
+                for (input_idx, &weight) in neuron.weights.iter().enumerate() {
+                    let (x1, y1) = position(layer_idx, input_idx);
+                    let magnitude = weight.abs() / max_weight;
+                    writeln!(
+                        svg,
+                        "<line x1=\"{x1}\" y1=\"{y1}\" x2=\"{x2}\" y2=\"{y2}\" stroke=\"{}\" stroke-width=\"{:.2}\" stroke-opacity=\"{:.2}\"/>",
+                        weight_color(weight),
+                        0.25 + magnitude * (SVG_MAX_STROKE - 0.25),
+                        opacity(magnitude) as f32 / 255.0,
+                    )
+                    .unwrap();
+                }
+            }
+        }
+
+        for (layer_idx, layer) in topology.iter().enumerate() {
+            for neuron_idx in 0..layer.neurons {
+                let (x, y) = position(layer_idx, neuron_idx);
+                writeln!(
+                    svg,
+                    "<circle cx=\"{x}\" cy=\"{y}\" r=\"{SVG_NEURON_RADIUS}\" fill=\"#d3d3ed\" stroke=\"black\"/>"
+                )
+                .unwrap();
+
+                // Only the outer layers are labelled, placed outside of the network.
+                let label = neuron_label(labels, &topology, layer_idx, neuron_idx);
+                if layer_idx == 0 {
+                    let x = x - 1.5 * SVG_NEURON_RADIUS;
+                    writeln!(
+                        svg,
+                        "<text x=\"{x}\" y=\"{y}\" font-family=\"monospace\" font-size=\"12\" text-anchor=\"end\" dominant-baseline=\"middle\">{}</text>",
+                        escape_svg(&label)
+                    )
+                    .unwrap();
+                } else if layer_idx == topology.len() - 1 {
+                    let x = x + 1.5 * SVG_NEURON_RADIUS;
+                    writeln!(
+                        svg,
+                        "<text x=\"{x}\" y=\"{y}\" font-family=\"monospace\" font-size=\"12\" text-anchor=\"start\" dominant-baseline=\"middle\">{}</text>",
+                        escape_svg(&label)
+                    )
+                    .unwrap();
+                }
+            }
+        }
+
+        writeln!(svg, "</svg>").unwrap();
+        svg
+    }
+
+    // Largest weight magnitude, used to normalize edges. Biases aren't drawn so they're skipped.
+    fn max_abs_weight(&self) -> f32 {
+        self.layers
+            .iter()
+            .flat_map(|layer| layer.neurons.iter())
+            .flat_map(|neuron| neuron.weights.iter())
+            .fold(0.0_f32, |max, weight| max.max(weight.abs()))
+            .max(f32::EPSILON)
+    }
+}
+
+fn neuron_label(
+    labels: &Labels,
+    topology: &[LayerTopology],
+    layer_idx: usize,
+    neuron_idx: usize,
+) -> String {
+    if layer_idx == 0 {
+        labels
+            .inputs
+            .get(neuron_idx)
+            .cloned()
+            .unwrap_or_else(|| format!("in {neuron_idx}"))
+    } else if layer_idx == topology.len() - 1 {
+        labels
+            .outputs
+            .get(neuron_idx)
+            .cloned()
+            .unwrap_or_else(|| format!("out {neuron_idx}"))
+    } else {
+        String::new()
+    }
+}
+
+fn weight_color(weight: f32) -> &'static str {
+    if weight >= 0.0 {
+        POSITIVE_COLOR
+    } else {
+        NEGATIVE_COLOR
+    }
+}
+
+// Maps a normalized magnitude to an alpha channel, keeping weak edges faintly visible.
+fn opacity(magnitude: f32) -> u8 {
+    (40.0 + magnitude.clamp(0.0, 1.0) * 215.0) as u8
+}
+
+// Labels are user provided, DOT strings only need quotes and backslashes escaped.
+fn escape_dot(label: &str) -> String {
+    label.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+// SVG text is XML, so markup characters are replaced by their entities.
+fn escape_svg(label: &str) -> String {
+    label
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn network() -> Network {
+        let topology = [LayerTopology { neurons: 2 }, LayerTopology { neurons: 1 }];
+        Network::from_weights(&topology, [0.1, 0.5, -1.0])
+    }
+
+    #[test]
+    fn to_dot() {
+        let labels = Labels::new(vec!["left".into(), "right".into()], vec!["speed".into()]);
+        let dot = network().to_dot(&labels);
+
+        assert!(dot.starts_with("digraph network {"));
+        assert!(dot.contains("n0_0 [label=\"left\"];"));
+        assert!(dot.contains("n0_1 [label=\"right\"];"));
+        assert!(dot.contains("n1_0 [label=\"speed\"];"));
+        assert!(dot.contains("n0_0 -> n1_0 [penwidth=2.12, color=\"#2166ac93\"];"));
+        assert!(dot.contains("n0_1 -> n1_0 [penwidth=4.00, color=\"#b2182bff\"];"));
+    }
+
+    #[test]
+    fn to_dot_escapes_labels() {
+        let labels = Labels::new(vec!["a<b".into(), "say \"hi\" \\o/".into()], vec![]);
+        let dot = network().to_dot(&labels);
+
+        assert!(dot.contains("n0_0 [label=\"a<b\"];"));
+        assert!(dot.contains("n0_1 [label=\"say \\\"hi\\\" \\\\o/\"];"));
+    }
+
+    #[test]
+    fn to_dot_without_labels() {
+        let dot = network().to_dot(&Labels::default());
+
+        assert!(dot.contains("n0_1 [label=\"in 1\"];"));
+        assert!(dot.contains("n1_0 [label=\"out 0\"];"));
+    }
+
+    #[test]
+    fn to_svg() {
+        let labels = Labels::new(vec!["a<b".into()], vec![]);
+        let svg = network().to_svg(&labels);
+
+        assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\""));
+        assert!(svg.trim_end().ends_with("</svg>"));
+        assert_eq!(svg.matches("<circle").count(), 3);
+        assert_eq!(svg.matches("<line").count(), 2);
+        assert!(svg.contains(">a&lt;b</text>"));
+        assert!(svg.contains(">out 0</text>"));
+    }
+}
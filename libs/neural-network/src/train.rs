@@ -0,0 +1,389 @@
+use crate::*;
+
+const ADAM_BETA1: f32 = 0.9;
+const ADAM_BETA2: f32 = 0.999;
+const ADAM_EPSILON: f32 = 1e-8;
+
+/// Optimizer describes types that update the weights of a network from their gradients.
+///
+/// Both slices are flattened in the order of [`Network::weights`].
+pub trait Optimizer {
+    fn step(&mut self, weights: &mut [f32], gradients: &[f32]);
+}
+
+/// Sgd represents the plain stochastic gradient descent.
+#[derive(Debug, Clone)]
+pub struct Sgd {
+    learning_rate: f32,
+}
+
+impl Sgd {
+    pub fn new(learning_rate: f32) -> Self {
+        assert!(learning_rate > 0.0);
+        Self { learning_rate }
+    }
+}
+
+impl Optimizer for Sgd {
+    fn step(&mut self, weights: &mut [f32], gradients: &[f32]) {
+        assert_eq!(weights.len(), gradients.len());
+
+        for (weight, gradient) in weights.iter_mut().zip(gradients) {
+            *weight -= self.learning_rate * gradient;
+        }
+    }
+}
+
+/// Adam represents gradient descent with adaptive moment estimation.
+///
+/// Moments are sized lazily on the first step, so an instance should only be used with a single
+/// network.
+#[derive(Debug, Clone)]
+pub struct Adam {
+    learning_rate: f32,
+    beta1: f32,
+    beta2: f32,
+    epsilon: f32,
+    steps: i32,
+    m: Vec<f32>, // First moment (mean) of the gradients
+    v: Vec<f32>, // Second moment (uncentered variance) of the gradients
+}
+
+impl Adam {
+    /// Creates Adam with the default decay rates from the original paper.
+    pub fn new(learning_rate: f32) -> Self {
+        Self::with_betas(learning_rate, ADAM_BETA1, ADAM_BETA2)
+    }
+
+    pub fn with_betas(learning_rate: f32, beta1: f32, beta2: f32) -> Self {
+        assert!(learning_rate > 0.0);
+        assert!((0.0..1.0).contains(&beta1));
+        assert!((0.0..1.0).contains(&beta2));
+
+        Self {
+            learning_rate,
+            beta1,
+            beta2,
+            epsilon: ADAM_EPSILON,
+            steps: 0,
+            m: Vec::new(),
+            v: Vec::new(),
+        }
+    }
+}
+
+impl Optimizer for Adam {
+    fn step(&mut self, weights: &mut [f32], gradients: &[f32]) {
+        assert_eq!(weights.len(), gradients.len());
+
+        if self.m.len() != weights.len() {
+            self.m = vec![0.0; weights.len()];
+            self.v = vec![0.0; weights.len()];
+            self.steps = 0;
+        }
+        self.steps += 1;
+
+        // Bias corrections, as both moments start at zero.
+        let correction1 = 1.0 - self.beta1.powi(self.steps);
+        let correction2 = 1.0 - self.beta2.powi(self.steps);
+
+        for (i, (weight, &gradient)) in weights.iter_mut().zip(gradients).enumerate() {
+            self.m[i] = self.beta1 * self.m[i] + (1.0 - self.beta1) * gradient;
+            self.v[i] = self.beta2 * self.v[i] + (1.0 - self.beta2) * gradient * gradient;
+
+            let m = self.m[i] / correction1;
+            let v = self.v[i] / correction2;
+            *weight -= self.learning_rate * m / (v.sqrt() + self.epsilon);
+        }
+    }
+}
+
+/// Returns the mean squared error between the outputs and the targets.
+pub fn mse(outputs: &[f32], targets: &[f32]) -> f32 {
+    assert_eq!(outputs.len(), targets.len());
+
+    if outputs.is_empty() {
+        return 0.0;
+    }
+
+    outputs
+        .iter()
+        .zip(targets)
+        .map(|(output, target)| (output - target).powi(2))
+        .sum::<f32>()
+        / outputs.len() as f32
+}
+
+impl Network {
+    /// Computes the mean squared error of a single sample along with its gradient for every
+    /// weight, flattened in the order of [`Network::weights`].
+    pub fn backpropagate(
+        &self,
+        inputs: Vec<f32>,
+        targets: &[f32],
+    ) -> Result<(f32, Vec<f32>), NetworkError> {
+        let activations = self.try_propagate_traced(inputs)?;
+        let outputs = &activations[activations.len() - 1];
+
+        if outputs.len() != targets.len() {
+            return Err(NetworkError::TargetSizeMismatch {
+                expected: outputs.len(),
+                actual: targets.len(),
+            });
+        }
+
+        let loss = mse(outputs, targets);
+
+        // Gradients of the loss over each layer's outputs, starting with d(MSE)/d(output).
+        let mut deltas: Vec<f32> = outputs
+            .iter()
+            .zip(targets)
+            .map(|(output, target)| 2.0 * (output - target) / outputs.len() as f32)
+            .collect();
+
+        let mut layer_gradients = Vec::with_capacity(self.layers.len());
+
+        for (layer_idx, layer) in self.layers.iter().enumerate().rev() {
+            let inputs = &activations[layer_idx];
+            let outputs = &activations[layer_idx + 1];
+            let mut gradients = Vec::with_capacity(layer.neurons.len() * (inputs.len() + 1));
+            let mut input_deltas = vec![0.0; inputs.len()];
+
+            for ((neuron, &output), &delta) in layer.neurons.iter().zip(outputs).zip(&deltas) {
+                // Hidden ReLU neurons only pass gradients through if they fired, the linear output
+                // layer always does.
+                let fired = layer_idx + 1 == self.layers.len() || output > 0.0;
+                let delta = if fired { delta } else { 0.0 };
+
+                gradients.push(delta);
+                gradients.extend(inputs.iter().map(|input| delta * input));
+
+                for (input_delta, weight) in input_deltas.iter_mut().zip(&neuron.weights) {
+                    *input_delta += delta * weight;
+                }
+            }
+
+            layer_gradients.push(gradients);
+            deltas = input_deltas;
+        }
+
+        let gradients = layer_gradients.into_iter().rev().flatten().collect();
+        Ok((loss, gradients))
+    }
+
+    /// Performs a single optimizer step over the averaged gradients of a batch of
+    /// `(inputs, targets)` samples, returning the mean loss before the update.
+    pub fn train_batch(
+        &mut self,
+        optimizer: &mut dyn Optimizer,
+        batch: &[(Vec<f32>, Vec<f32>)],
+    ) -> f32 {
+        self.try_train_batch(optimizer, batch)
+            .unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Fallible version of [`Network::train_batch`].
+    pub fn try_train_batch(
+        &mut self,
+        optimizer: &mut dyn Optimizer,
+        batch: &[(Vec<f32>, Vec<f32>)],
+    ) -> Result<f32, NetworkError> {
+        if batch.is_empty() {
+            return Ok(0.0);
+        }
+
+        let mut total_loss = 0.0;
+        let mut total_gradients = vec![0.0; self.weights().count()];
+
+        for (inputs, targets) in batch {
+            let (loss, gradients) = self.backpropagate(inputs.clone(), targets)?;
+            total_loss += loss;
+            for (total, gradient) in total_gradients.iter_mut().zip(gradients) {
+                *total += gradient;
+            }
+        }
+
+        let scale = 1.0 / batch.len() as f32;
+        total_gradients
+            .iter_mut()
+            .for_each(|gradient| *gradient *= scale);
+
+        let mut weights: Vec<f32> = self.weights().collect();
+        optimizer.step(&mut weights, &total_gradients);
+        self.set_weights(&weights);
+
+        Ok(total_loss * scale)
+    }
+
+    /// Applies Oja's rule, a normalized variant of the Hebbian rule, over every weight using the
+    /// activations returned by [`Network::propagate_traced`]. Connections between neurons firing
+    /// together are strengthened, while the decay term keeps weights from growing unbounded.
+    /// Biases are left untouched.
+    ///
+    /// As ReLU activations have no upper bound, they're squashed with `tanh` beforehand; Oja's
+    /// rule only converges for small enough `rate * output²`.
+    pub fn hebbian_update(&mut self, activations: &[Vec<f32>], rate: f32) {
+        assert_eq!(activations.len(), self.layers.len() + 1);
+
+        for (layer, adj_activations) in self.layers.iter_mut().zip(activations.windows(2)) {
+            let (inputs, outputs) = (&adj_activations[0], &adj_activations[1]);
+            for (neuron, &output) in layer.neurons.iter_mut().zip(outputs) {
+                let output = output.tanh();
+                for (weight, &input) in neuron.weights.iter_mut().zip(inputs) {
+                    *weight += rate * output * (input.tanh() - output * *weight);
+                }
+            }
+        }
+    }
+
+    // Writes back flattened weights in the order of `weights()`.
+    fn set_weights(&mut self, weights: &[f32]) {
+        let mut weights = weights.iter().copied();
+
+        for neuron in self
+            .layers
+            .iter_mut()
+            .flat_map(|layer| layer.neurons.iter_mut())
+        {
+            neuron.bias = weights.next().expect("got not enough bias!");
+            for weight in neuron.weights.iter_mut() {
+                *weight = weights.next().expect("got not enough weights!");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    fn topology() -> [LayerTopology; 3] {
+        [
+            LayerTopology { neurons: 2 },
+            LayerTopology { neurons: 4 },
+            LayerTopology { neurons: 1 },
+        ]
+    }
+
+    // Targets of a simple linear function.
+    fn samples() -> Vec<(Vec<f32>, Vec<f32>)> {
+        (0..16)
+            .map(|i| {
+                let a = (i % 4) as f32 / 4.0;
+                let b = (i / 4) as f32 / 4.0;
+                (vec![a, b], vec![0.5 * a + 0.25 * b + 0.1])
+            })
+            .collect()
+    }
+
+    fn loss(network: &Network) -> f32 {
+        samples()
+            .iter()
+            .map(|(inputs, targets)| mse(&network.propagate(inputs.clone()), targets))
+            .sum::<f32>()
+            / samples().len() as f32
+    }
+
+    #[test]
+    fn mse_loss() {
+        assert_relative_eq!(mse(&[1.0, 2.0], &[0.0, 4.0]), 2.5);
+        assert_relative_eq!(mse(&[], &[]), 0.0);
+    }
+
+    #[test]
+    fn backpropagate_matches_finite_differences() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let network = Network::random(&mut rng, &topology());
+        let (inputs, targets) = &samples()[5];
+        let (_, gradients) = network.backpropagate(inputs.clone(), targets).unwrap();
+
+        let weights: Vec<f32> = network.weights().collect();
+        let epsilon = 1e-3;
+
+        for (i, gradient) in gradients.iter().enumerate() {
+            let mut shifted = weights.clone();
+            shifted[i] += epsilon;
+            let above = Network::from_weights(&topology(), shifted.clone());
+            shifted[i] -= 2.0 * epsilon;
+            let below = Network::from_weights(&topology(), shifted);
+
+            let numeric = (mse(&above.propagate(inputs.clone()), targets)
+                - mse(&below.propagate(inputs.clone()), targets))
+                / (2.0 * epsilon);
+            assert_relative_eq!(*gradient, numeric, epsilon = 1e-3);
+        }
+    }
+
+    #[test]
+    fn hebbian_update() {
+        let topology = [LayerTopology { neurons: 2 }, LayerTopology { neurons: 1 }];
+        let mut network = Network::from_weights(&topology, [0.0, 0.5, 0.5]);
+        let activations = network.propagate_traced(vec![1.0, 0.0]);
+        network.hebbian_update(&activations, 0.1);
+
+        // Only the connection from the active input is strengthened, the silent one decays.
+        let actual: Vec<_> = network.weights().collect();
+        assert_relative_eq!(
+            actual.as_slice(),
+            [0.0, 0.52451694, 0.4893224].as_ref(),
+            epsilon = 1e-6
+        );
+    }
+
+    #[test]
+    fn hebbian_update_stays_bounded() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut network = Network::random(&mut rng, &topology());
+
+        for _ in 0..10_000 {
+            let activations = network.propagate_traced(vec![50.0, 20.0]);
+            network.hebbian_update(&activations, 0.5);
+        }
+
+        assert!(network.weights().all(|weight| weight.abs() <= 2.0));
+    }
+
+    #[test]
+    fn backpropagate_target_mismatch() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let network = Network::random(&mut rng, &topology());
+        assert_eq!(
+            network.backpropagate(vec![0.0, 0.0], &[0.0, 0.0]),
+            Err(NetworkError::TargetSizeMismatch {
+                expected: 1,
+                actual: 2
+            })
+        );
+    }
+
+    #[test]
+    fn sgd_reduces_loss() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut network = Network::random(&mut rng, &topology());
+        let mut optimizer = Sgd::new(0.1);
+        let initial = loss(&network);
+
+        for _ in 0..200 {
+            network.train_batch(&mut optimizer, &samples());
+        }
+
+        assert!(loss(&network) < initial * 0.1);
+    }
+
+    #[test]
+    fn adam_reduces_loss() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut network = Network::random(&mut rng, &topology());
+        let mut optimizer = Adam::new(0.01);
+        let initial = loss(&network);
+
+        for _ in 0..200 {
+            network.train_batch(&mut optimizer, &samples());
+        }
+
+        assert!(loss(&network) < initial * 0.1);
+    }
+}
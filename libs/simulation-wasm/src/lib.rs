@@ -19,7 +19,9 @@ impl SimulationWasm {
     pub fn new(config_contents: &str) -> Self {
         let mut rng = thread_rng();
         let settings = Config::parse_config(config_contents);
-        let sim = sim::Simulation::random(&mut rng, settings);
+        let mut sim = sim::Simulation::random(&mut rng, settings);
+        // Obstacles are listed along the configuration, as `[[obstacles]]` tables.
+        sim.set_map(&mut rng, &sim::Map::parse_map(config_contents));
 
         Self { rng, sim, settings }
     }
@@ -28,16 +30,88 @@ impl SimulationWasm {
         WorldWasm::from(self.sim.world())
     }
 
+    /// Returns the brain of the selected animal, along with its current activations.
+    pub fn brain(&self, index: usize) -> Option<BrainWasm> {
+        let animal = self.sim.world().animals().get(index)?;
+        let activations = self.sim.trace_brain(index)?;
+        Some(BrainWasm::new(animal.brain(), activations))
+    }
+
+    /// Returns the best fitness of every MAP-Elites cell, if enabled.
+    pub fn heatmap(&self) -> Option<HeatmapWasm> {
+        self.sim.map_elites().map(|archive| {
+            let dimensions = archive.dimensions();
+            HeatmapWasm {
+                rows: dimensions[0].bins,
+                columns: dimensions[1].bins,
+                fitness: archive
+                    .heatmap()
+                    .into_iter()
+                    .map(|fitness| fitness.unwrap_or(f32::NAN))
+                    .collect(),
+            }
+        })
+    }
+
+    /// Loads the elite of given MAP-Elites cell into the displayed world.
+    pub fn load_elite(&mut self, speed_bin: usize, turning_bin: usize) -> bool {
+        self.sim
+            .load_elite(&mut self.rng, self.settings, &[speed_bin, turning_bin])
+    }
+
+    /// Returns the fittest animal of the last generation as a TOML champion to be saved, for the
+    /// benchmark of `lib-simulation`.
+    pub fn champion(&self, name: &str) -> Option<String> {
+        self.sim
+            .champion(name, self.settings)
+            .map(|champion| champion.to_toml())
+    }
+
     pub fn step(&mut self) {
         self.sim.step(&mut self.rng, self.settings);
     }
 
     pub fn fast_forward(&mut self) -> String {
         let stats = self.sim.fast_forward(&mut self.rng, self.settings);
-        format!(
+        let mut summary = format!(
             "Fitness : min {:.4}, max {:.4}, average {:4}, std {:4}",
             stats.min_fitness, stats.max_fitness, stats.avg_fitness, stats.fitness_std
-        )
+        );
+        // Species are only clustered under species selection.
+        if stats.species_count() > 0 {
+            summary += &format!(
+                "\nSpecies : {}, sizes {:?}",
+                stats.species_count(),
+                stats.species_sizes
+            );
+        }
+
+        // Only reporting the Pareto front in multi-objective evolution.
+        let front = self.sim.pareto_front();
+        if !front.is_empty() {
+            summary += &format!("\nPareto front : {} animals", front.len());
+            for point in front {
+                let [food, energy, spread] = point.objectives[..] else {
+                    continue;
+                };
+                summary += &format!(
+                    "\n  food {food:.0}, energy {:.4}, spread {:.4}",
+                    -energy, -spread
+                );
+            }
+        }
+
+        // Only detailing islands when there's more than a single one.
+        let island_stats = self.sim.island_statistics();
+        if island_stats.len() > 1 {
+            for (i, stats) in island_stats.iter().enumerate() {
+                summary += &format!(
+                    "\n  Island {i} : min {:.4}, max {:.4}, average {:4}, std {:4}",
+                    stats.min_fitness, stats.max_fitness, stats.avg_fitness, stats.fitness_std
+                );
+            }
+        }
+        summary
     }
 }
 
@@ -49,6 +123,9 @@ pub struct WorldWasm {
 
     #[wasm_bindgen(getter_with_clone)]
     pub foods: Vec<FoodWasm>,
+
+    #[wasm_bindgen(getter_with_clone)]
+    pub obstacles: Vec<ObstacleWasm>,
 }
 
 #[wasm_bindgen]
@@ -66,12 +143,99 @@ pub struct FoodWasm {
     pub y: f32,
 }
 
+// Circles use `x`, `y` and `radius`, rectangles and walls span from `x`, `y` to `x2`, `y2`.
+#[wasm_bindgen]
+#[derive(Clone, Debug)]
+pub struct ObstacleWasm {
+    #[wasm_bindgen(getter_with_clone)]
+    pub shape: String, // "circle", "rect" or "segment"
+    pub x: f32,
+    pub y: f32,
+    pub x2: f32,
+    pub y2: f32,
+    pub radius: f32,
+}
+
+#[wasm_bindgen]
+#[derive(Clone, Debug)]
+pub struct HeatmapWasm {
+    pub rows: usize,    // Average speed bins
+    pub columns: usize, // Turning rate bins
+
+    // Row-major fitness of every cell, NaN where no elite was found.
+    #[wasm_bindgen(getter_with_clone)]
+    pub fitness: Vec<f32>,
+}
+
+#[wasm_bindgen]
+#[derive(Clone, Debug)]
+pub struct BrainWasm {
+    // First layer holds the inputs (vision) and has no neurons.
+    #[wasm_bindgen(getter_with_clone)]
+    pub layers: Vec<LayerWasm>,
+}
+
+#[wasm_bindgen]
+#[derive(Clone, Debug)]
+pub struct LayerWasm {
+    #[wasm_bindgen(getter_with_clone)]
+    pub activations: Vec<f32>,
+
+    #[wasm_bindgen(getter_with_clone)]
+    pub neurons: Vec<NeuronWasm>,
+}
+
+#[wasm_bindgen]
+#[derive(Clone, Debug)]
+pub struct NeuronWasm {
+    pub bias: f32,
+
+    #[wasm_bindgen(getter_with_clone)]
+    pub weights: Vec<f32>,
+}
+
+impl BrainWasm {
+    fn new(brain: &sim::Brain, activations: Vec<Vec<f32>>) -> Self {
+        let mut activations = activations.into_iter();
+        let inputs = LayerWasm {
+            activations: activations.next().unwrap_or_default(),
+            neurons: Vec::new(),
+        };
+        let layers =
+            brain
+                .network()
+                .layers()
+                .iter()
+                .zip(activations)
+                .map(|(layer, activations)| LayerWasm {
+                    activations,
+                    neurons: layer
+                        .neurons()
+                        .iter()
+                        .map(|neuron| NeuronWasm {
+                            bias: neuron.bias(),
+                            weights: neuron.weights().to_vec(),
+                        })
+                        .collect(),
+                });
+
+        Self {
+            layers: std::iter::once(inputs).chain(layers).collect(),
+        }
+    }
+}
+
 // Converting library types into proxy interface with wasm-bindgen
 impl From<&sim::World> for WorldWasm {
     fn from(world: &sim::World) -> Self {
         let animals = world.animals().iter().map(AnimalWasm::from).collect();
         let foods = world.foods().iter().map(FoodWasm::from).collect();
-        Self { animals, foods }
+        let obstacles = world.obstacles().iter().map(ObstacleWasm::from).collect();
+        Self {
+            animals,
+            foods,
+            obstacles,
+        }
     }
 }
 
@@ -93,3 +257,21 @@ impl From<&sim::Food> for FoodWasm {
         }
     }
 }
+
+impl From<&sim::Obstacle> for ObstacleWasm {
+    fn from(obstacle: &sim::Obstacle) -> Self {
+        let (shape, from, to, radius) = match *obstacle {
+            sim::Obstacle::Circle { center, radius } => ("circle", center, center, radius),
+            sim::Obstacle::Rect { min, max } => ("rect", min, max, 0.0),
+            sim::Obstacle::Segment { start, end } => ("segment", start, end, 0.0),
+        };
+        Self {
+            shape: shape.to_string(),
+            x: from.x,
+            y: from.y,
+            x2: to.x,
+            y2: to.y,
+            radius,
+        }
+    }
+}
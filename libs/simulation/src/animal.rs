@@ -3,11 +3,23 @@ use crate::*;
 #[derive(Debug)]
 pub struct Animal {
     pub(crate) eye: Eye,
+    pub(crate) senses: Senses,
     pub(crate) brain: Brain,
     pub(crate) position: na::geometry::Point2<f32>,
     pub(crate) rotation: na::geometry::Rotation2<f32>,
     pub(crate) speed: f32,
     pub(crate) hunger: usize,
+    pub(crate) collisions: usize, // Steps spent bumping into obstacles
+    pub(crate) collision_penalty: f32,
+    // Chromosome the animal was born with, as lifetime learning alters the brain's weights.
+    pub(crate) genome: ga::Chromosome,
+    pub(crate) plasticity: PlasticityConfig,
+    pub(crate) learning_rate: f32,
+    pub(crate) metabolic_cost: f32, // Cost of the senses, dividing the food eaten
+    pub(crate) flocking: Flocking,
+    pub(crate) noise: Noise,
+    pub(crate) call: f32, // Volume of the call heard by others, loudest right after eating
+    pub(crate) trail: Trail,
 }
 
 impl PartialEq for Animal {
@@ -20,34 +32,193 @@ impl PartialEq for Animal {
 impl Animal {
     pub fn random(rng: &mut dyn RngCore, settings: Config) -> Self {
         let eye = Eye::config_new(settings);
-        let brain = Brain::random(rng, &eye);
-        Self::new(rng, eye, brain)
+        let brain = Brain::random(rng, &eye, &Senses::config_new(settings));
+
+        // Genes of the traits beyond the brain share the initial range of weights.
+        let traits = Self::count_traits(settings);
+        let traits: Vec<f32> = (0..traits).map(|_| rng.gen_range(-1.0..=1.0)).collect();
+        let genome = brain.as_chromosome().into_iter().chain(traits).collect();
+
+        Self::from_chromosome(rng, settings, genome)
+    }
+
+    pub(crate) fn as_chromosome(&self) -> ga::Chromosome {
+        match self.plasticity.inheritance {
+            Inheritance::Baldwinian => self.genome.clone(),
+            Inheritance::Lamarckian => {
+                let traits = self
+                    .genome
+                    .iter()
+                    .skip(Brain::count_weights(&self.eye, &self.senses));
+                self.brain
+                    .as_chromosome()
+                    .into_iter()
+                    .chain(traits.copied())
+                    .collect()
+            }
+        }
     }
 
-    fn new(rng: &mut dyn RngCore, eye: Eye, brain: Brain) -> Self {
+    /// Decodes the chromosome as the brain's weights, followed by the genes of the traits.
+    pub(crate) fn from_chromosome(
+        rng: &mut dyn RngCore,
+        settings: Config,
+        chromosome: ga::Chromosome,
+    ) -> Self {
         let config = AnimalConfig::default();
+        let eye = Eye::config_new(settings);
+        let senses = Senses::config_new(settings);
+        let brain_len = Brain::count_weights(&eye, &senses);
+        let brain_genes: ga::Chromosome = chromosome.iter().take(brain_len).copied().collect();
+        let traits: Vec<f32> = chromosome.iter().skip(brain_len).copied().collect();
+        let mut traits = traits.into_iter();
+
+        let plasticity = settings.plasticity;
+        let learning_rate = match plasticity.mode {
+            PlasticityMode::None => 0.0,
+            _ => decode_gene(
+                traits.next().expect("got no learning rate gene!"),
+                0.0,
+                plasticity.max_learning_rate,
+            ),
+        };
+
+        let sensory = settings.sensory;
+        let (eye, metabolic_cost) = if sensory.evolvable {
+            let fov_range = decode_gene(
+                traits.next().expect("got no field of view range gene!"),
+                sensory.min_fov_range,
+                sensory.max_fov_range,
+            );
+            let fov_angle = decode_gene(
+                traits.next().expect("got no field of view angle gene!"),
+                sensory.min_fov_angle,
+                sensory.max_fov_angle,
+            );
+            let cost = sensory.range_cost * fov_range + sensory.angle_cost * fov_angle;
+            (eye.with_fov(fov_range, fov_angle), cost)
+        } else {
+            (eye, 0.0)
+        };
+        let flocking = Flocking::from_genes(&mut traits, settings);
+        let brain = Brain::from_chromosome(brain_genes, &eye, &senses);
+
         Self {
             eye,
+            senses,
             brain,
             position: rng.r#gen(),
             rotation: rng.r#gen(),
             speed: config.speed,
             hunger: 0,
+            collisions: 0,
+            collision_penalty: settings.collisions.penalty,
+            genome: chromosome,
+            plasticity,
+            learning_rate,
+            metabolic_cost,
+            flocking,
+            noise: Noise::config_new(settings),
+            call: 0.0,
+            trail: Trail::config_new(settings),
         }
     }
 
-    pub(crate) fn as_chromosome(&self) -> ga::Chromosome {
-        self.brain.as_chromosome()
+    // Number of genes appended after the brain's weights.
+    pub(crate) fn count_traits(settings: Config) -> usize {
+        let learning_rate = match settings.plasticity.mode {
+            PlasticityMode::None => 0,
+            _ => 1,
+        };
+        let sensory = if settings.sensory.evolvable { 2 } else { 0 };
+        learning_rate + sensory + Flocking::count_genes(settings)
     }
 
-    pub(crate) fn from_chromosome(
-        rng: &mut dyn RngCore,
-        settings: Config,
-        chromosome: ga::Chromosome,
-    ) -> Self {
-        let eye = Eye::config_new(settings);
-        let brain = Brain::from_chromosome(chromosome, &eye);
-        Self::new(rng, eye, brain)
+    /// Returns the brain's inputs: the eye cells of every channel, then the other senses. The
+    /// animal is found at `index` among the surroundings' animals, so that it doesn't sense
+    /// itself.
+    pub fn perceive(&self, index: usize, surroundings: &Surroundings) -> Vec<f32> {
+        let mut inputs = self.eye.calc_vision(
+            self.position,
+            self.rotation,
+            surroundings.foods,
+            &surroundings.other_positions(index),
+            surroundings.obstacles,
+        );
+        inputs.extend(self.senses.sense(self, index, surroundings));
+        inputs
+    }
+
+    /// Propagates the vision through the brain and returns its outputs, learning along the way
+    /// when plasticity is enabled.
+    pub(crate) fn think(&mut self, vision: Vec<f32>) -> Vec<f32> {
+        match self.plasticity.mode {
+            PlasticityMode::None => self.brain.nn.propagate(vision),
+            PlasticityMode::Hebbian => {
+                let trace = self.brain.nn.propagate_traced(vision);
+                self.brain.nn.hebbian_update(&trace, self.learning_rate);
+                trace[trace.len() - 1].clone()
+            }
+            PlasticityMode::Reward => {
+                // Blending the activations into an eligibility trace until food gets eaten, so
+                // that the steps leading to the food get reinforced too.
+                let trace = self.brain.nn.propagate_traced(vision);
+                let outputs = trace[trace.len() - 1].clone();
+                self.brain.remember(trace, self.plasticity.trace_decay);
+                outputs
+            }
+        }
+    }
+
+    /// Eats a food, reinforcing the recent thoughts in reward-modulated plasticity.
+    pub(crate) fn eat(&mut self) {
+        self.hunger += 1;
+        self.call = 1.0;
+
+        if self.plasticity.mode == PlasticityMode::Reward && !self.brain.trace.is_empty() {
+            let rate = self.learning_rate * self.plasticity.reward;
+            self.brain.nn.hebbian_update(&self.brain.trace, rate);
+        }
+    }
+
+    /// Applies the brain outputs (speed and rotation shifts) to the animal, limited by the
+    /// configured accelerations and speed range.
+    pub(crate) fn steer(&mut self, output: &[f32], config: &SimulationConfig) {
+        let speed = output[0].clamp(-config.speed_accel, config.speed_accel);
+        let angle = output[1].clamp(-config.rotation_accel, config.rotation_accel);
+
+        self.speed = (self.speed + speed).clamp(config.speed_min, config.speed_max);
+        self.rotation = na::Rotation2::new(self.rotation.angle() + angle);
+        self.trail.record_turn(angle);
+    }
+
+    pub fn eye(&self) -> &Eye {
+        &self.eye
+    }
+
+    pub fn senses(&self) -> &Senses {
+        &self.senses
+    }
+
+    pub fn brain(&self) -> &Brain {
+        &self.brain
+    }
+
+    /// Returns the behaviour of the animal so far, as described for novelty search.
+    pub fn behaviour(&self) -> ga::BehaviourDescriptor {
+        self.trail.descriptor(self.position)
+    }
+
+    /// Returns the objectives of the animal so far, all to be maximized: food eaten, negated
+    /// energy spent and negated distance to the flock's centre.
+    pub fn objectives(&self) -> Vec<f32> {
+        let (energy, spread) = self.trail.effort();
+        vec![self.hunger as f32, -energy, -spread]
+    }
+
+    /// Returns the average speed and turning rate of the animal so far, binned by MAP-Elites.
+    pub fn motion(&self) -> ga::BehaviourDescriptor {
+        self.trail.motion()
     }
 
     pub fn position(&self) -> na::Point2<f32> {
@@ -61,4 +232,134 @@ impl Animal {
     pub fn speed(&self) -> f32 {
         self.speed
     }
+
+    pub fn call(&self) -> f32 {
+        self.call
+    }
+
+    pub fn learning_rate(&self) -> f32 {
+        self.learning_rate
+    }
+
+    pub fn flocking(&self) -> &Flocking {
+        &self.flocking
+    }
+
+    pub fn metabolic_cost(&self) -> f32 {
+        self.metabolic_cost
+    }
+
+    pub fn collisions(&self) -> usize {
+        self.collisions
+    }
+
+    /// Returns the food eaten so far, divided by the metabolic cost of the senses and the
+    /// penalty of the collisions on top of one.
+    ///
+    /// Dividing rather than subtracting keeps the fitness positive for roulette selection to spin
+    /// on, while a cheaper animal eating as much still ranks higher, however costly both are.
+    pub fn fitness(&self) -> f32 {
+        let penalty = self.collision_penalty * self.collisions as f32;
+        self.hunger as f32 / (1.0 + self.metabolic_cost + penalty)
+    }
+}
+
+// Maps an unbounded gene into the `[min, max]` range with a logistic curve, so mutations can
+// never push a trait out of its range.
+pub(crate) fn decode_gene(gene: f32, min: f32, max: f32) -> f32 {
+    min + (max - min) / (1.0 + (-gene).exp())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    fn genes(chromosome: &ga::Chromosome) -> Vec<f32> {
+        chromosome.iter().copied().collect()
+    }
+
+    fn learned_animal(inheritance: &str) -> Animal {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let settings = Config::parse_config(&format!(
+            "[plasticity]\nmode = \"hebbian\"\ninheritance = \"{inheritance}\""
+        ));
+        let mut animal = Animal::random(&mut rng, settings);
+        let cells = animal.eye.cells();
+
+        for step in 0..10 {
+            let vision = (0..cells).map(|cell| ((cell + step) % 3) as f32).collect();
+            animal.think(vision);
+        }
+        animal
+    }
+
+    #[test]
+    fn test_baldwinian_inheritance() {
+        let animal = learned_animal("baldwinian");
+
+        assert!(animal.learning_rate() > 0.0);
+        assert_ne!(
+            genes(&animal.brain.as_chromosome()),
+            genes(&animal.genome)[..animal.genome.len() - 1]
+        );
+        assert_eq!(genes(&animal.as_chromosome()), genes(&animal.genome));
+    }
+
+    #[test]
+    fn test_lamarckian_inheritance() {
+        let animal = learned_animal("lamarckian");
+        let chromosome = genes(&animal.as_chromosome());
+        let brain = genes(&animal.brain.as_chromosome());
+
+        assert_eq!(chromosome.len(), animal.genome.len());
+        assert_eq!(chromosome[..brain.len()], brain[..]);
+        assert_eq!(chromosome.last(), genes(&animal.genome).last());
+    }
+
+    #[test]
+    fn test_evolvable_senses() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let settings = Config::parse_config(
+            "
+            [sensory]
+            evolvable = true
+            min_fov_range = 0.2
+            max_fov_range = 0.4
+            range_cost = 10.0
+            angle_cost = 1.0
+            ",
+        );
+        let mut animal = Animal::random(&mut rng, settings);
+        let eye = &animal.eye;
+
+        assert_eq!(
+            animal.genome.len(),
+            Brain::count_weights(eye, &animal.senses) + 2
+        );
+        assert!((0.2..=0.4).contains(&eye.fov_range()));
+        assert!(
+            (settings.sensory.min_fov_angle..=settings.sensory.max_fov_angle)
+                .contains(&eye.fov_angle())
+        );
+        let cost = 10.0 * eye.fov_range() + eye.fov_angle();
+        assert!((animal.metabolic_cost() - cost).abs() < 1e-6);
+
+        // Fitness stays positive however costly the senses, cheaper ones ranking higher.
+        assert_eq!(animal.fitness(), 0.0);
+        animal.hunger = 2;
+        assert!((animal.fitness() - 2.0 / (1.0 + cost)).abs() < 1e-6);
+        assert!(animal.fitness() > 0.0);
+        let fitness = animal.fitness();
+        animal.metabolic_cost *= 2.0;
+        assert!(animal.fitness() < fitness);
+    }
+
+    #[test]
+    fn test_decode_gene() {
+        assert_eq!(decode_gene(0.0, 0.0, 2.0), 1.0);
+        assert!(decode_gene(100.0, 0.0, 2.0) <= 2.0);
+        assert!(decode_gene(-100.0, 0.0, 2.0) >= 0.0);
+    }
 }
@@ -1,18 +1,29 @@
 use crate::*;
 
+#[derive(Clone, Debug)]
 pub struct AnimalIndividual {
     fitness: f32,
     chromosome: ga::Chromosome,
+    behaviour: ga::BehaviourDescriptor,
+    objectives: Vec<f32>,
 }
 
 impl AnimalIndividual {
     pub fn from_animal(animal: &Animal) -> Self {
         Self {
-            fitness: animal.hunger as f32,
-            chromosome: animal.brain.as_chromosome(),
+            fitness: animal.fitness(),
+            chromosome: animal.as_chromosome(),
+            behaviour: animal.behaviour(),
+            objectives: animal.objectives(),
         }
     }
 
+    /// Replaces the fitness earned in the displayed world, such as by evaluation rollouts.
+    pub(crate) fn with_fitness(mut self, fitness: f32) -> Self {
+        self.fitness = fitness;
+        self
+    }
+
     pub fn into_animal(self, rng: &mut dyn RngCore, settings: Config) -> Animal {
         Animal::from_chromosome(rng, settings, self.chromosome)
     }
@@ -23,6 +34,8 @@ impl ga::Individual for AnimalIndividual {
         Self {
             fitness: 0.0,
             chromosome,
+            behaviour: ga::BehaviourDescriptor::default(),
+            objectives: Vec::new(),
         }
     }
     fn fitness(&self) -> f32 {
@@ -32,3 +45,15 @@ impl ga::Individual for AnimalIndividual {
         &self.chromosome
     }
 }
+
+impl ga::Behaviour for AnimalIndividual {
+    fn behaviour(&self) -> &ga::BehaviourDescriptor {
+        &self.behaviour
+    }
+}
+
+impl ga::MultiObjective for AnimalIndividual {
+    fn objectives(&self) -> &[f32] {
+        &self.objectives
+    }
+}
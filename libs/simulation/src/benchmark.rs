@@ -0,0 +1,286 @@
+use crate::*;
+
+// Two-sided 95% quantiles of Student's t distribution, for 1 to 30 degrees of freedom.
+const T_95: [f32; 30] = [
+    12.706, 4.303, 3.182, 2.776, 2.571, 2.447, 2.365, 2.306, 2.262, 2.228, 2.201, 2.179, 2.160,
+    2.145, 2.131, 2.120, 2.110, 2.101, 2.093, 2.086, 2.080, 2.074, 2.069, 2.064, 2.060, 2.056,
+    2.052, 2.048, 2.045, 2.042,
+];
+
+/// ConfidenceInterval represents the mean of a measure over the benchmark's layouts, within a
+/// 95% confidence margin of Student's t distribution, as layouts are few.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ConfidenceInterval {
+    pub mean: f32,
+    pub margin: f32,
+}
+
+impl ConfidenceInterval {
+    pub fn new(samples: &[f32]) -> Self {
+        assert!(!samples.is_empty());
+
+        let len = samples.len() as f32;
+        let mean = samples.iter().sum::<f32>() / len;
+        if samples.len() == 1 {
+            return Self { mean, margin: 0.0 };
+        }
+        let variance = samples.iter().map(|x| (x - mean).powi(2)).sum::<f32>() / (len - 1.0);
+        Self {
+            mean,
+            margin: Self::t_95(samples.len() - 1) * (variance / len).sqrt(),
+        }
+    }
+
+    // Degrees of freedom past the table round down to the closest listed quantile, erring on
+    // the wider side.
+    fn t_95(freedom: usize) -> f32 {
+        match freedom {
+            1..=30 => T_95[freedom - 1],
+            31..40 => T_95[29],
+            40..60 => 2.021,
+            60..120 => 2.000,
+            _ => 1.980,
+        }
+    }
+
+    pub fn low(&self) -> f32 {
+        self.mean - self.margin
+    }
+
+    pub fn high(&self) -> f32 {
+        self.mean + self.margin
+    }
+}
+
+/// BenchmarkReport represents how a champion did over every layout of a benchmark, averaged per
+/// animal of its flock.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BenchmarkReport {
+    pub name: String,
+    pub food_eaten: ConfidenceInterval,
+    pub distance_flown: ConfidenceInterval,
+    pub time_to_first_food: ConfidenceInterval, // In steps, the whole layout if none was eaten
+}
+
+/// Benchmark runs champions on a fixed suite of seeded food layouts, so that champions of
+/// different trainings compare on the same ground.
+///
+/// Every layout is flown by a flock of copies of the champion, as many as the animals of a
+/// world, for as many steps as a generation.
+#[derive(Debug, Clone)]
+pub struct Benchmark {
+    layouts: usize,
+    steps: usize,
+    obstacles: Vec<Obstacle>,
+}
+
+impl Benchmark {
+    pub fn config_new(settings: Config) -> Self {
+        assert!(settings.benchmark.layouts > 0);
+
+        Self {
+            layouts: settings.benchmark.layouts,
+            steps: settings.simulation.max_generation + 1,
+            obstacles: Vec::new(),
+        }
+    }
+
+    /// Places the obstacles of the map in every layout.
+    pub fn with_map(mut self, map: &Map) -> Self {
+        self.obstacles = map.obstacles.clone();
+        self
+    }
+
+    /// Runs every champion on the same layouts, in order, failing on the first champion that
+    /// can't be flown under the settings.
+    pub fn compare(
+        &self,
+        settings: Config,
+        champions: &[Champion],
+    ) -> Result<Vec<BenchmarkReport>, ChampionError> {
+        champions
+            .iter()
+            .map(|champion| self.evaluate(settings, champion))
+            .collect()
+    }
+
+    pub fn evaluate(
+        &self,
+        settings: Config,
+        champion: &Champion,
+    ) -> Result<BenchmarkReport, ChampionError> {
+        champion.validate(settings)?;
+
+        let layouts: Vec<(f32, f32, f32)> = (0..self.layouts as u64)
+            .map(|seed| self.layout(seed, settings, champion))
+            .collect();
+        let measure = |pick: fn(&(f32, f32, f32)) -> f32| {
+            let samples: Vec<f32> = layouts.iter().map(pick).collect();
+            ConfidenceInterval::new(&samples)
+        };
+
+        Ok(BenchmarkReport {
+            name: champion.name.clone(),
+            food_eaten: measure(|layout| layout.0),
+            distance_flown: measure(|layout| layout.1),
+            time_to_first_food: measure(|layout| layout.2),
+        })
+    }
+
+    /// Runs the champion under every noise level, each setting the standard deviation of the
+    /// noise on the eye cells and on both brain outputs, giving its robustness curve.
+    pub fn robustness(
+        &self,
+        settings: Config,
+        champion: &Champion,
+        levels: &[f32],
+    ) -> Result<Vec<(f32, BenchmarkReport)>, ChampionError> {
+        levels
+            .iter()
+            .map(|&level| {
+                let mut settings = settings;
+                settings.noise.vision = level;
+                settings.noise.speed = level;
+                settings.noise.rotation = level;
+                Ok((level, self.evaluate(settings, champion)?))
+            })
+            .collect()
+    }
+
+    // Returns the food eaten, distance flown and time to first food, averaged over the flock.
+    fn layout(&self, seed: u64, settings: Config, champion: &Champion) -> (f32, f32, f32) {
+        let mut rng = ChaCha8Rng::seed_from_u64(seed);
+        let chromosomes = vec![champion.chromosome(); settings.world.num_animals];
+        let mut world = World::from_chromosomes(&mut rng, settings, &chromosomes);
+        world.set_obstacles(&mut rng, self.obstacles.clone());
+
+        let flock = world.animals.len();
+        let mut distances = vec![0.0; flock];
+        let mut first_foods = vec![self.steps; flock];
+        for step in 0..self.steps {
+            let positions = world.positions();
+            Simulation::step_world(&mut world, &settings.simulation, &mut rng);
+
+            for (index, animal) in world.animals.iter().enumerate() {
+                distances[index] += wrapped_distance(positions[index], animal.position);
+                if animal.hunger > 0 {
+                    first_foods[index] = first_foods[index].min(step + 1);
+                }
+            }
+        }
+
+        let food: usize = world.animals.iter().map(|animal| animal.hunger).sum();
+        (
+            food as f32 / flock as f32,
+            distances.iter().sum::<f32>() / flock as f32,
+            first_foods.iter().sum::<usize>() as f32 / flock as f32,
+        )
+    }
+}
+
+// Distance between two positions of the wrapping world, taking the shortest way around.
+fn wrapped_distance(a: na::Point2<f32>, b: na::Point2<f32>) -> f32 {
+    let delta = (b - a).map(|d| d - d.round());
+    delta.magnitude()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand::SeedableRng;
+
+    fn settings() -> Config {
+        Config::parse_config(
+            "
+            [simulation]
+            max_generation = 50
+
+            [world]
+            num_animals = 5
+            num_foods = 500
+
+            [benchmark]
+            layouts = 4
+            ",
+        )
+    }
+
+    #[test]
+    fn test_confidence_interval() {
+        let interval = ConfidenceInterval::new(&[2.0, 4.0, 6.0, 8.0]);
+
+        assert_eq!(interval.mean, 5.0);
+        // Three degrees of freedom.
+        assert_eq!(interval.margin, 3.182 * (20.0_f32 / 12.0).sqrt());
+        assert!(interval.low() < 5.0 && interval.high() > 5.0);
+        assert_eq!(ConfidenceInterval::new(&[3.0]).margin, 0.0);
+    }
+
+    #[test]
+    fn test_wrapped_distance() {
+        let a = na::Point2::new(0.95, 0.5);
+        let b = na::Point2::new(0.05, 0.5);
+
+        assert!((wrapped_distance(a, b) - 0.1).abs() < 1e-6);
+        assert!((wrapped_distance(b, a) - 0.1).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_compare() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let settings = settings();
+        let benchmark = Benchmark::config_new(settings);
+        let champions: Vec<Champion> = ["first", "second"]
+            .iter()
+            .map(|name| {
+                let animal = Animal::random(&mut rng, settings);
+                Champion::new(name, 0.0, &animal.as_chromosome(), settings)
+            })
+            .collect();
+
+        let reports = benchmark.compare(settings, &champions).unwrap();
+
+        assert_eq!(reports.len(), 2);
+        assert_eq!(reports[1].name, "second");
+        // Fixed layouts give the same report for the same champion.
+        assert_eq!(
+            reports[0],
+            benchmark.evaluate(settings, &champions[0]).unwrap()
+        );
+        for report in &reports {
+            assert!(report.distance_flown.mean > 0.0);
+            assert!(report.time_to_first_food.mean <= 51.0);
+        }
+    }
+
+    #[test]
+    fn test_robustness() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let settings = settings();
+        let benchmark = Benchmark::config_new(settings);
+        let animal = Animal::random(&mut rng, settings);
+        let champion = Champion::new("sparrow", 0.0, &animal.as_chromosome(), settings);
+
+        let curve = benchmark
+            .robustness(settings, &champion, &[0.0, 0.5])
+            .unwrap();
+
+        assert_eq!(curve.len(), 2);
+        assert_eq!(curve[0].0, 0.0);
+        assert_eq!(curve[0].1, benchmark.evaluate(settings, &champion).unwrap());
+        assert_ne!(curve[1].1, curve[0].1);
+    }
+
+    #[test]
+    fn test_evaluate_mismatched_champion() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let settings = settings();
+        let animal = Animal::random(&mut rng, settings);
+        let champion = Champion::new("sparrow", 0.0, &animal.as_chromosome(), settings);
+        let other = Config::parse_config("[senses]\nsmell = true");
+
+        let result = Benchmark::config_new(other).evaluate(other, &champion);
+
+        assert!(matches!(result, Err(ChampionError::InputsMismatch { .. })));
+    }
+}
@@ -3,35 +3,172 @@ use crate::*;
 #[derive(Debug)]
 pub struct Brain {
     pub(crate) nn: nn::Network,
+    // Decaying average of the past activations, only kept by reward-modulated plasticity.
+    pub(crate) trace: Vec<Vec<f32>>,
 }
 
 impl Brain {
-    pub fn random(rng: &mut dyn RngCore, eye: &Eye) -> Self {
+    pub fn random(rng: &mut dyn RngCore, eye: &Eye, senses: &Senses) -> Self {
         Self {
-            nn: nn::Network::random(rng, &Self::topology(eye)),
+            nn: nn::Network::random(rng, &Self::topology(eye.inputs() + senses.inputs())),
+            trace: Vec::new(),
         }
     }
 
-    pub(crate) fn from_chromosome(chromosome: ga::Chromosome, eye: &Eye) -> Self {
+    pub(crate) fn from_chromosome(chromosome: ga::Chromosome, eye: &Eye, senses: &Senses) -> Self {
         Self {
-            nn: nn::Network::from_weights(&Self::topology(eye), chromosome),
+            nn: nn::Network::from_weights(
+                &Self::topology(eye.inputs() + senses.inputs()),
+                chromosome,
+            ),
+            trace: Vec::new(),
         }
     }
 
-    fn topology(eye: &Eye) -> [nn::LayerTopology; 3] {
+    // Blends new activations into the trace, keeping `decay` of the previous ones.
+    pub(crate) fn remember(&mut self, activations: Vec<Vec<f32>>, decay: f32) {
+        if self.trace.is_empty() {
+            self.trace = activations;
+            return;
+        }
+        for (traced, layer) in self.trace.iter_mut().zip(activations) {
+            for (traced, activation) in traced.iter_mut().zip(layer) {
+                *traced = decay * *traced + (1.0 - decay) * activation;
+            }
+        }
+    }
+
+    /// Returns the number of genes taken by the brain's weights.
+    pub(crate) fn count_weights(eye: &Eye, senses: &Senses) -> usize {
+        nn::Network::count_weights(&Self::topology(eye.inputs() + senses.inputs()))
+    }
+
+    /// Builds the network of a brain with given inputs from its genes, failing if they don't fit.
+    pub(crate) fn try_network(
+        inputs: usize,
+        genes: &[f32],
+    ) -> Result<nn::Network, nn::NetworkError> {
+        nn::Network::try_from_weights(&Self::topology(inputs), genes.iter().copied())
+    }
+
+    // Inputs are the eye cells of every channel, followed by the inputs of every other sense.
+    fn topology(inputs: usize) -> [nn::LayerTopology; 3] {
         [
+            nn::LayerTopology { neurons: inputs },
             nn::LayerTopology {
-                neurons: eye.cells(),
-            },
-            nn::LayerTopology {
-                neurons: 2 * eye.cells(),
+                neurons: 2 * inputs,
             },
             nn::LayerTopology { neurons: 2 },
         ]
     }
 
+    pub fn network(&self) -> &nn::Network {
+        &self.nn
+    }
+
+    /// Trains the brain on `(vision, action)` pairs for given epochs of shuffled mini-batches,
+    /// returning the mean loss of the last epoch.
+    pub fn pretrain(
+        &mut self,
+        rng: &mut dyn RngCore,
+        optimizer: &mut dyn nn::Optimizer,
+        samples: &[(Vec<f32>, Vec<f32>)],
+        epochs: usize,
+        batch_size: usize,
+    ) -> f32 {
+        assert!(batch_size > 0);
+
+        let mut samples = samples.to_vec();
+        let mut loss = 0.0;
+
+        for _ in 0..epochs {
+            samples.shuffle(rng);
+            let batches = samples.chunks(batch_size);
+            let count = batches.len().max(1);
+            loss = batches
+                .map(|batch| self.nn.train_batch(optimizer, batch))
+                .sum::<f32>()
+                / count as f32;
+        }
+
+        loss
+    }
+
+    /// Renders the brain as Graphviz DOT, with inputs named after eye cells of every channel
+    /// and senses.
+    pub fn to_dot(&self, eye: &Eye, senses: &Senses) -> String {
+        self.nn.to_dot(&Self::labels(eye, senses))
+    }
+
+    /// Renders the brain as a standalone SVG image, with inputs named after eye cells and senses.
+    pub fn to_svg(&self, eye: &Eye, senses: &Senses) -> String {
+        self.nn.to_svg(&Self::labels(eye, senses))
+    }
+
+    fn labels(eye: &Eye, senses: &Senses) -> nn::Labels {
+        nn::Labels::new(
+            eye.labels().into_iter().chain(senses.labels()).collect(),
+            vec!["speed".to_string(), "rotation".to_string()],
+        )
+    }
+
     pub fn as_chromosome(&self) -> ga::Chromosome {
         // Type infered by `.collect()` as Chromosome implements the FromIterator<f32>
         self.nn.weights().collect()
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    #[test]
+    fn test_dot_labels() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let eye = Eye::new(&0.5, &FRAC_PI_4, &3);
+        let senses = Senses::new();
+        let dot = Brain::random(&mut rng, &eye, &senses).to_dot(&eye, &senses);
+
+        assert!(dot.contains("n0_0 [label=\"eye[0]\"];"));
+        assert!(dot.contains("n0_2 [label=\"eye[2]\"];"));
+        assert!(dot.contains("n2_0 [label=\"speed\"];"));
+        assert!(dot.contains("n2_1 [label=\"rotation\"];"));
+    }
+
+    #[test]
+    fn test_remember() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let eye = Eye::new(&0.5, &FRAC_PI_4, &3);
+        let mut brain = Brain::random(&mut rng, &eye, &Senses::new());
+
+        brain.remember(vec![vec![1.0, 0.0]], 0.8);
+        brain.remember(vec![vec![0.0, 1.0]], 0.8);
+
+        // The first step is still remembered after the second one.
+        assert!((brain.trace[0][0] - 0.8).abs() < 1e-6);
+        assert!((brain.trace[0][1] - 0.2).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_channels_topology() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let eye = Eye::new(&0.5, &FRAC_PI_4, &3).with_channel(Channel::Animals);
+        let senses = Senses::new();
+        let brain = Brain::random(&mut rng, &eye, &senses);
+        let dot = brain.to_dot(&eye, &senses);
+
+        // 6 inputs, 12 hidden neurons and 2 outputs, with biases.
+        assert_eq!(
+            Brain::count_weights(&eye, &senses),
+            (6 + 1) * 12 + (12 + 1) * 2
+        );
+        assert_eq!(
+            brain.as_chromosome().len(),
+            Brain::count_weights(&eye, &senses)
+        );
+        assert!(dot.contains("n0_2 [label=\"eye[2]\"];"));
+        assert!(dot.contains("n0_3 [label=\"flock[0]\"];"));
+    }
+}
@@ -0,0 +1,241 @@
+use crate::*;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Errors reported when loading or flying a saved [`Champion`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum ChampionError {
+    /// The contents aren't a champion saved with [`Champion::to_toml`].
+    Parse(String),
+    /// The champion's brain was trained on another number of inputs than the settings give.
+    InputsMismatch { expected: usize, actual: usize },
+    /// The champion carries another number of trait genes than the settings decode.
+    TraitsMismatch { expected: usize, actual: usize },
+    /// The champion carries as many trait genes as the settings decode, but other ones.
+    TraitSetsMismatch {
+        expected: TraitSets,
+        actual: TraitSets,
+    },
+    /// The genes don't fit the brain of the champion's layout.
+    Genes(nn::NetworkError),
+}
+
+impl fmt::Display for ChampionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Parse(err) => write!(f, "got unparsable champion! {err}"),
+            Self::InputsMismatch { expected, actual } => {
+                write!(
+                    f,
+                    "got wrong brain inputs! expected {expected}, got {actual}"
+                )
+            }
+            Self::TraitsMismatch { expected, actual } => {
+                write!(
+                    f,
+                    "got wrong trait count! expected {expected}, got {actual}"
+                )
+            }
+            Self::TraitSetsMismatch { expected, actual } => {
+                write!(
+                    f,
+                    "got wrong trait sets! expected {expected:?}, got {actual:?}"
+                )
+            }
+            Self::Genes(err) => write!(f, "got genes not fitting the brain! {err}"),
+        }
+    }
+}
+
+impl std::error::Error for ChampionError {}
+
+/// TraitSets records which sets of trait genes follow the brain's weights, in this order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TraitSets {
+    pub learning_rate: bool,
+    pub sensory: bool, // Range and angle of the field of view
+    pub flocking: bool,
+    pub flocking_radius: bool, // Separation radius, after the flocking weights
+}
+
+impl TraitSets {
+    pub fn config_new(settings: Config) -> Self {
+        Self {
+            learning_rate: settings.plasticity.mode != PlasticityMode::None,
+            sensory: settings.sensory.evolvable,
+            flocking: settings.flocking.evolvable,
+            flocking_radius: settings.flocking.evolvable && settings.flocking.evolvable_radius,
+        }
+    }
+}
+
+/// Champion represents a saved chromosome, along with the fitness it reached in training.
+///
+/// The gene layout, the brain's input count and the number and sets of trait genes following
+/// its weights, is saved too so that a champion isn't decoded under settings it wasn't trained
+/// with.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Champion {
+    pub name: String,
+    pub fitness: f32,
+    pub inputs: usize,
+    pub traits: usize,
+    #[serde(default)]
+    pub trait_sets: TraitSets,
+    pub genes: Vec<f32>,
+}
+
+impl Champion {
+    pub fn new(name: &str, fitness: f32, chromosome: &ga::Chromosome, settings: Config) -> Self {
+        let (inputs, traits) = Self::layout(settings);
+        Self {
+            name: name.to_string(),
+            fitness,
+            inputs,
+            traits,
+            trait_sets: TraitSets::config_new(settings),
+            genes: chromosome.iter().copied().collect(),
+        }
+    }
+
+    pub fn from_summary(summary: &RunSummary, settings: Config) -> Self {
+        Self::new(
+            "champion",
+            summary.best_fitness,
+            &summary.best_chromosome,
+            settings,
+        )
+    }
+
+    /// Loads a champion saved with [`Champion::to_toml`], checking that its genes fit its
+    /// layout.
+    pub fn parse_champion(contents: &str) -> Result<Self, ChampionError> {
+        let champion: Self =
+            toml::from_str(contents).map_err(|err| ChampionError::Parse(err.to_string()))?;
+        champion.check_genes()?;
+        Ok(champion)
+    }
+
+    pub fn to_toml(&self) -> String {
+        toml::to_string(self).expect("got champion that can't be saved")
+    }
+
+    pub fn chromosome(&self) -> ga::Chromosome {
+        self.genes.iter().copied().collect()
+    }
+
+    /// Checks that the champion can be flown under given settings: its layout must be the one
+    /// the settings give, and its genes must fit it.
+    pub fn validate(&self, settings: Config) -> Result<(), ChampionError> {
+        let (inputs, traits) = Self::layout(settings);
+        if self.inputs != inputs {
+            return Err(ChampionError::InputsMismatch {
+                expected: inputs,
+                actual: self.inputs,
+            });
+        }
+        if self.traits != traits {
+            return Err(ChampionError::TraitsMismatch {
+                expected: traits,
+                actual: self.traits,
+            });
+        }
+        let trait_sets = TraitSets::config_new(settings);
+        if self.trait_sets != trait_sets {
+            return Err(ChampionError::TraitSetsMismatch {
+                expected: trait_sets,
+                actual: self.trait_sets,
+            });
+        }
+        self.check_genes()
+    }
+
+    // The genes are the brain's weights followed by the traits.
+    fn check_genes(&self) -> Result<(), ChampionError> {
+        let brain_len = self.genes.len().saturating_sub(self.traits);
+        Brain::try_network(self.inputs, &self.genes[..brain_len])
+            .map(|_| ())
+            .map_err(ChampionError::Genes)
+    }
+
+    // Returns the brain's input count and the number of trait genes under given settings.
+    fn layout(settings: Config) -> (usize, usize) {
+        let inputs = Eye::config_new(settings).inputs() + Senses::config_new(settings).inputs();
+        (inputs, Animal::count_traits(settings))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_save_and_load() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let settings = Config::parse_config("");
+        let chromosome = Animal::random(&mut rng, settings).as_chromosome();
+        let champion = Champion::new("sparrow", 12.0, &chromosome, settings);
+
+        let saved = champion.to_toml();
+        let loaded = Champion::parse_champion(&saved).unwrap();
+
+        assert_eq!(loaded, champion);
+        assert!(loaded.chromosome().iter().eq(chromosome.iter()));
+        assert_eq!(loaded.validate(settings), Ok(()));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(matches!(
+            Champion::parse_champion("name = 12"),
+            Err(ChampionError::Parse(_))
+        ));
+        assert!(matches!(
+            Champion::parse_champion(
+                "name = \"sparrow\"\nfitness = 1.0\ninputs = 2\ntraits = 0\ngenes = [0.5]"
+            ),
+            Err(ChampionError::Genes(
+                nn::NetworkError::NotEnoughWeights { .. }
+            ))
+        ));
+    }
+
+    #[test]
+    fn test_validate() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let settings = Config::parse_config("");
+        let chromosome = Animal::random(&mut rng, settings).as_chromosome();
+        let champion = Champion::new("sparrow", 0.0, &chromosome, settings);
+
+        let evolvable = Config::parse_config("[sensory]\nevolvable = true");
+        assert_eq!(
+            champion.validate(evolvable),
+            Err(ChampionError::TraitsMismatch {
+                expected: champion.traits + 2,
+                actual: champion.traits,
+            })
+        );
+
+        // As many trait genes, but a learning rate and the field of view instead of flocking.
+        let flocking = Config::parse_config("[flocking]\nevolvable = true");
+        let learning =
+            Config::parse_config("[plasticity]\nmode = \"hebbian\"\n[sensory]\nevolvable = true");
+        let flocker = Champion::new("sparrow", 0.0, &chromosome, flocking);
+        assert_eq!(
+            flocker.validate(learning),
+            Err(ChampionError::TraitSetsMismatch {
+                expected: TraitSets::config_new(learning),
+                actual: flocker.trait_sets,
+            })
+        );
+
+        let mut truncated = champion.clone();
+        truncated.genes.pop();
+        assert!(matches!(
+            truncated.validate(settings),
+            Err(ChampionError::Genes(_))
+        ));
+    }
+}
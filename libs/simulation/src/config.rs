@@ -1,7 +1,6 @@
 use crate::*;
 use serde::Deserialize;
 use std::f32::consts::PI;
-use toml;
 
 const CONFIG_FILE: &str = "config.toml";
 
@@ -22,6 +21,55 @@ const CELLS: usize = 10;
 
 const SPEED: f32 = 0.002;
 
+const MIN_FOV_RANGE: f32 = 0.1;
+const MAX_FOV_RANGE: f32 = 1.0;
+const MIN_FOV_ANGLE: f32 = FRAC_PI_4;
+const MAX_FOV_ANGLE: f32 = 2.0 * PI;
+
+const COHERENCE: f32 = 0.1;
+const SEPARATION: f32 = 0.55;
+const ALIGNMENT: f32 = 0.1;
+const SEPARATION_RADIUS: f32 = 0.01;
+const MAX_FLOCKING_WEIGHT: f32 = 1.0;
+const MAX_SEPARATION_RADIUS: f32 = 0.05;
+
+const SMELL_RANGE: f32 = 0.3;
+const HEARING_RANGE: f32 = 0.3;
+
+const COLLISION_PENALTY: f32 = 0.1;
+
+const ISLANDS: usize = 1;
+const MIGRATION_INTERVAL: usize = 5;
+const MIGRANTS: usize = 2;
+const MAX_OVERRIDES: usize = 8;
+
+const MAX_LEARNING_RATE: f32 = 0.01;
+const REWARD: f32 = 1.0;
+const TRACE_DECAY: f32 = 0.8;
+
+const SPECIES_THRESHOLD: f32 = 6.0;
+const SHARING_SIGMA: f32 = 6.0;
+const SHARING_ALPHA: f32 = 1.0;
+
+const NEIGHBOURS: usize = 15;
+const ARCHIVE_THRESHOLD: f32 = 0.1;
+const ARCHIVE_CAPACITY: usize = 500;
+const FITNESS_WEIGHT: f32 = 0.5;
+const CHECKPOINTS: usize = 8;
+const GRID: usize = 8;
+
+const CMA_ES_SIGMA: f32 = 0.5;
+
+const DIFFERENTIAL_WEIGHT: f32 = 0.5;
+const CROSSOVER_PROBABILITY: f32 = 0.9;
+
+const GENERATIONS: usize = 100;
+
+const LAYOUTS: usize = 30;
+
+const SPEED_BINS: usize = 10;
+const TURNING_BINS: usize = 10;
+
 #[derive(Debug, Deserialize, Clone, Copy)]
 #[serde(default)]
 pub struct SimulationConfig {
@@ -31,9 +79,21 @@ pub struct SimulationConfig {
     pub rotation_accel: f32,
     pub mutation_chance: f32,
     pub mutation_weight: f32,
+    pub crossover: CrossoverKind,
     pub max_generation: usize,
 }
 
+/// CrossoverKind selects how the genes of two parents are mixed into an offspring.
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum CrossoverKind {
+    /// Every gene comes from either parent with equal chance.
+    #[default]
+    Uniform,
+    /// Genes come from the first parent up to a random cut point, then from the second one.
+    SinglePoint,
+}
+
 #[derive(Debug, Deserialize, Clone, Copy)]
 #[serde(default)]
 pub struct WorldConfig {
@@ -47,12 +107,369 @@ pub struct AnimalConfig {
     pub speed: f32,
 }
 
+/// EyeMode selects how the eye fills its cells.
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum EyeMode {
+    /// Sums the energy of everything within range per angular cell, seeing through anything.
+    #[default]
+    Cells,
+    /// Casts one ray per cell, only seeing the nearest object hit.
+    Raycast,
+}
+
 #[derive(Debug, Deserialize, Clone, Copy)]
 #[serde(default)]
 pub struct EyeConfig {
     pub fov_range: f32,
     pub fov_angle: f32,
     pub cells: usize,
+    pub animals: bool,   // Adds cells sensing the other animals
+    pub obstacles: bool, // Adds cells sensing the obstacles
+    pub mode: EyeMode,
+}
+
+/// Per-animal field of view encoded by genes, instead of the `[eye]` one shared by every animal.
+/// Seeing further or wider costs the given amount of food per unit of range or radian.
+#[derive(Debug, Deserialize, Clone, Copy)]
+#[serde(default)]
+pub struct SensoryConfig {
+    pub evolvable: bool,
+    pub min_fov_range: f32,
+    pub max_fov_range: f32,
+    pub min_fov_angle: f32,
+    pub max_fov_angle: f32,
+    pub range_cost: f32,
+    pub angle_cost: f32,
+}
+
+/// Weights of the boid rules, shared by every animal unless encoded by genes of their own.
+#[derive(Debug, Deserialize, Clone, Copy)]
+#[serde(default)]
+pub struct FlockingConfig {
+    pub evolvable: bool,
+    pub evolvable_radius: bool, // Also encodes the separation radius, when evolvable
+    pub coherence: f32,
+    pub separation: f32,
+    pub alignment: f32,
+    pub separation_radius: f32,
+    pub max_weight: f32,
+    pub max_separation_radius: f32,
+}
+
+/// Senses feeding the brain besides the eye, their inputs appended after the eye cells in
+/// this order.
+#[derive(Debug, Deserialize, Clone, Copy)]
+#[serde(default)]
+pub struct SensesConfig {
+    pub proprioception: bool,
+    pub smell: bool,
+    pub hearing: bool,
+    pub smell_range: f32,
+    pub hearing_range: f32,
+}
+
+/// Noise on the eye cells and brain outputs of every animal, zero being exact.
+#[derive(Debug, Deserialize, Clone, Copy, Default)]
+#[serde(default)]
+pub struct NoiseConfig {
+    pub vision: f32,
+    pub dropout: f32,
+    pub speed: f32,
+    pub rotation: f32,
+    pub latency: usize,
+}
+
+/// Pressure to avoid the obstacles, the shapes themselves being read by [`Map::parse_map`].
+#[derive(Debug, Deserialize, Clone, Copy)]
+#[serde(default)]
+pub struct CollisionConfig {
+    pub penalty: f32, // Cost of every step spent bumping into an obstacle, dividing the food eaten
+}
+
+/// IslandTopology selects which islands receive the migrants of an island.
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum IslandTopology {
+    #[default]
+    Ring,
+    FullyConnected,
+}
+
+impl From<IslandTopology> for ga::MigrationTopology {
+    fn from(topology: IslandTopology) -> Self {
+        match topology {
+            IslandTopology::Ring => Self::Ring,
+            IslandTopology::FullyConnected => Self::FullyConnected,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Clone, Copy)]
+#[serde(default)]
+pub struct IslandConfig {
+    pub count: usize,
+    pub migration_interval: usize,
+    pub migrants: usize,
+    pub topology: IslandTopology,
+    pub overrides: IslandOverrides,
+}
+
+/// IslandOverride replaces some operators of one island, listed as `[[islands.overrides]]`
+/// tables in the order of the islands. Unset operators are the shared ones.
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq)]
+#[serde(default)]
+pub struct IslandOverride {
+    pub selection: Option<SelectionKind>,
+    pub mutation_chance: Option<f32>,
+    pub mutation_weight: Option<f32>,
+    pub crossover: Option<CrossoverKind>,
+}
+
+impl IslandOverride {
+    /// Returns the settings with the overridden operators in place of the shared ones.
+    pub fn apply(&self, mut settings: Config) -> Config {
+        if let Some(selection) = self.selection {
+            settings.selection.method = selection;
+        }
+        if let Some(mutation_chance) = self.mutation_chance {
+            settings.simulation.mutation_chance = mutation_chance;
+        }
+        if let Some(mutation_weight) = self.mutation_weight {
+            settings.simulation.mutation_weight = mutation_weight;
+        }
+        if let Some(crossover) = self.crossover {
+            settings.simulation.crossover = crossover;
+        }
+        settings
+    }
+}
+
+/// IslandOverrides holds up to 8 `[[islands.overrides]]` tables in a fixed array, so that the
+/// settings stay `Copy`.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct IslandOverrides {
+    overrides: [IslandOverride; MAX_OVERRIDES],
+    len: usize,
+}
+
+impl IslandOverrides {
+    /// Returns the override of given island, the default one past the listed overrides.
+    pub fn get(&self, island: usize) -> IslandOverride {
+        self.as_slice().get(island).copied().unwrap_or_default()
+    }
+
+    pub fn as_slice(&self) -> &[IslandOverride] {
+        &self.overrides[..self.len]
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+impl<'de> Deserialize<'de> for IslandOverrides {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let listed = Vec::<IslandOverride>::deserialize(deserializer)?;
+        if listed.len() > MAX_OVERRIDES {
+            return Err(serde::de::Error::invalid_length(
+                listed.len(),
+                &"at most 8 island overrides",
+            ));
+        }
+
+        let mut overrides = Self {
+            len: listed.len(),
+            ..Self::default()
+        };
+        overrides.overrides[..listed.len()].copy_from_slice(&listed);
+        Ok(overrides)
+    }
+}
+
+/// PlasticityMode selects how brains learn within a lifetime, if at all.
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum PlasticityMode {
+    /// Brains are frozen within a generation.
+    #[default]
+    None,
+    /// Every step strengthens the connections of neurons firing together.
+    Hebbian,
+    /// Connections of the last few steps are strengthened only when food is eaten.
+    Reward,
+}
+
+/// Inheritance selects whether learned weights are passed on to the offsprings.
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum Inheritance {
+    /// Learned weights are discarded, offsprings inherit the innate ones.
+    #[default]
+    Baldwinian,
+    /// Learned weights are written back into the chromosome.
+    Lamarckian,
+}
+
+#[derive(Debug, Deserialize, Clone, Copy)]
+#[serde(default)]
+pub struct PlasticityConfig {
+    pub mode: PlasticityMode,
+    pub inheritance: Inheritance,
+    pub max_learning_rate: f32,
+    pub reward: f32,
+    pub trace_decay: f32,
+}
+
+/// SelectionKind selects how parents are picked from the evaluated population.
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum SelectionKind {
+    /// Roulette wheel over the raw fitness.
+    #[default]
+    Roulette,
+    /// Roulette wheel over fitness divided by the size of the animal's species.
+    Species,
+    /// Roulette wheel over fitness shared with every close enough animal.
+    Sharing,
+}
+
+#[derive(Debug, Deserialize, Clone, Copy)]
+#[serde(default)]
+pub struct SelectionConfig {
+    pub method: SelectionKind,
+    pub species_threshold: f32,
+    pub sharing_sigma: f32,
+    pub sharing_alpha: f32,
+}
+
+/// SearchMode selects what the genetic algorithm selects parents on.
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum SearchMode {
+    /// Food eaten only.
+    #[default]
+    Fitness,
+    /// Novelty of the behaviour only, ignoring food.
+    Novelty,
+    /// Weighted sum of normalized fitness and novelty.
+    Combined,
+}
+
+/// DescriptorKind selects how the behaviour of an animal is described for novelty search.
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum DescriptorKind {
+    /// Position at the end of the generation.
+    #[default]
+    FinalPosition,
+    /// Positions at evenly spaced checkpoints of the generation.
+    Trajectory,
+    /// Cells of a grid over the world visited during the generation.
+    Coverage,
+}
+
+#[derive(Debug, Deserialize, Clone, Copy)]
+#[serde(default)]
+pub struct NoveltyConfig {
+    pub mode: SearchMode,
+    pub descriptor: DescriptorKind,
+    pub neighbours: usize,
+    pub archive_threshold: f32,
+    pub archive_capacity: usize,
+    pub fitness_weight: f32,
+    pub checkpoints: usize,
+    pub grid: usize,
+}
+
+#[derive(Debug, Deserialize, Clone, Copy)]
+#[serde(default)]
+pub struct MapElitesConfig {
+    pub enabled: bool,
+    pub speed_bins: usize,
+    pub turning_bins: usize,
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, Default)]
+#[serde(default)]
+pub struct MultiObjectiveConfig {
+    pub enabled: bool,
+}
+
+#[derive(Debug, Deserialize, Clone, Copy)]
+#[serde(default)]
+pub struct CmaEsConfig {
+    pub enabled: bool,
+    pub sigma: f32,
+}
+
+/// DifferentialKind selects the base vector of differential evolution.
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum DifferentialKind {
+    /// DE/rand/1/bin, mutating a random animal.
+    #[default]
+    Rand1Bin,
+    /// DE/best/1/bin, mutating the fittest animal.
+    Best1Bin,
+}
+
+impl From<DifferentialKind> for ga::DifferentialVariant {
+    fn from(kind: DifferentialKind) -> Self {
+        match kind {
+            DifferentialKind::Rand1Bin => Self::Rand1Bin,
+            DifferentialKind::Best1Bin => Self::Best1Bin,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Clone, Copy)]
+#[serde(default)]
+pub struct DifferentialConfig {
+    pub enabled: bool,
+    pub variant: DifferentialKind,
+    pub f: f32,
+    pub cr: f32,
+}
+
+/// RolloutAggregate selects how the fitness of an animal over several rollouts is combined.
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum RolloutAggregate {
+    #[default]
+    Mean,
+    Median,
+    /// The lowest fitness, rewarding animals doing well on every food layout.
+    Worst,
+}
+
+/// Evaluation of every chromosome over several headless rollouts, never if `count` is zero.
+#[derive(Debug, Deserialize, Clone, Copy, Default)]
+#[serde(default)]
+pub struct RolloutConfig {
+    pub count: usize,
+    pub aggregate: RolloutAggregate,
+}
+
+#[derive(Debug, Deserialize, Clone, Copy)]
+#[serde(default)]
+pub struct BenchmarkConfig {
+    pub layouts: usize,
+}
+
+/// Stop conditions of a run, any of them ending it. Zero disables a condition.
+#[derive(Debug, Deserialize, Clone, Copy)]
+#[serde(default)]
+pub struct RunConfig {
+    pub generations: usize, // Length of the run, unlike `simulation.max_generation`
+    pub target_fitness: f32,
+    pub stagnation: usize,
+    pub time_budget: f32,
+    pub diversity_floor: f32,
 }
 
 #[derive(Debug, Deserialize, Clone, Copy)]
@@ -62,15 +479,63 @@ pub struct Config {
     #[serde(default)]
     pub eye: EyeConfig,
     #[serde(default)]
+    pub sensory: SensoryConfig,
+    #[serde(default)]
+    pub senses: SensesConfig,
+    #[serde(default)]
+    pub noise: NoiseConfig,
+    #[serde(default)]
+    pub flocking: FlockingConfig,
+    #[serde(default)]
     pub world: WorldConfig,
     #[serde(default)]
+    pub collisions: CollisionConfig,
+    #[serde(default)]
     pub animal: AnimalConfig,
+    #[serde(default)]
+    pub plasticity: PlasticityConfig,
+    #[serde(default)]
+    pub islands: IslandConfig,
+    #[serde(default)]
+    pub selection: SelectionConfig,
+    #[serde(default)]
+    pub novelty: NoveltyConfig,
+    #[serde(default)]
+    pub map_elites: MapElitesConfig,
+    #[serde(default)]
+    pub multi_objective: MultiObjectiveConfig,
+    #[serde(default)]
+    pub cma_es: CmaEsConfig,
+    #[serde(default)]
+    pub differential: DifferentialConfig,
+    #[serde(default)]
+    pub run: RunConfig,
+    #[serde(default)]
+    pub rollouts: RolloutConfig,
+    #[serde(default)]
+    pub benchmark: BenchmarkConfig,
 }
 
 impl Config {
     pub fn parse_config(config_contents: &str) -> Self {
         let config: Config = toml::from_str(config_contents)
-            .expect(format!("unable to parse {CONFIG_FILE}").as_str());
+            .unwrap_or_else(|_| panic!("unable to parse {CONFIG_FILE}"));
+
+        // At most one optimiser breeds in place of the islands' genetic algorithms, whose
+        // operators alone the overrides replace.
+        let optimisers = [
+            config.map_elites.enabled,
+            config.multi_objective.enabled,
+            config.cma_es.enabled,
+            config.differential.enabled,
+        ];
+        let optimised = optimisers.iter().filter(|enabled| **enabled).count();
+        if optimised > 1 {
+            panic!("got more than one optimiser enabled in {CONFIG_FILE}");
+        }
+        if optimised > 0 && !config.islands.overrides.is_empty() {
+            panic!("got island overrides without genetic algorithms in {CONFIG_FILE}");
+        }
         config
     }
 }
@@ -81,12 +546,162 @@ impl Default for AnimalConfig {
     }
 }
 
+impl Default for IslandConfig {
+    fn default() -> Self {
+        Self {
+            count: ISLANDS,
+            migration_interval: MIGRATION_INTERVAL,
+            migrants: MIGRANTS,
+            topology: IslandTopology::default(),
+            overrides: IslandOverrides::default(),
+        }
+    }
+}
+
+impl Default for SelectionConfig {
+    fn default() -> Self {
+        Self {
+            method: SelectionKind::default(),
+            species_threshold: SPECIES_THRESHOLD,
+            sharing_sigma: SHARING_SIGMA,
+            sharing_alpha: SHARING_ALPHA,
+        }
+    }
+}
+
+impl Default for CmaEsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            sigma: CMA_ES_SIGMA,
+        }
+    }
+}
+
+impl Default for DifferentialConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            variant: DifferentialKind::default(),
+            f: DIFFERENTIAL_WEIGHT,
+            cr: CROSSOVER_PROBABILITY,
+        }
+    }
+}
+
+impl Default for BenchmarkConfig {
+    fn default() -> Self {
+        Self { layouts: LAYOUTS }
+    }
+}
+
+impl Default for RunConfig {
+    fn default() -> Self {
+        Self {
+            generations: GENERATIONS,
+            target_fitness: 0.0,
+            stagnation: 0,
+            time_budget: 0.0,
+            diversity_floor: 0.0,
+        }
+    }
+}
+
+impl Default for MapElitesConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            speed_bins: SPEED_BINS,
+            turning_bins: TURNING_BINS,
+        }
+    }
+}
+
+impl Default for NoveltyConfig {
+    fn default() -> Self {
+        Self {
+            mode: SearchMode::default(),
+            descriptor: DescriptorKind::default(),
+            neighbours: NEIGHBOURS,
+            archive_threshold: ARCHIVE_THRESHOLD,
+            archive_capacity: ARCHIVE_CAPACITY,
+            fitness_weight: FITNESS_WEIGHT,
+            checkpoints: CHECKPOINTS,
+            grid: GRID,
+        }
+    }
+}
+
+impl Default for SensoryConfig {
+    fn default() -> Self {
+        Self {
+            evolvable: false,
+            min_fov_range: MIN_FOV_RANGE,
+            max_fov_range: MAX_FOV_RANGE,
+            min_fov_angle: MIN_FOV_ANGLE,
+            max_fov_angle: MAX_FOV_ANGLE,
+            range_cost: 0.0,
+            angle_cost: 0.0,
+        }
+    }
+}
+
+impl Default for CollisionConfig {
+    fn default() -> Self {
+        Self {
+            penalty: COLLISION_PENALTY,
+        }
+    }
+}
+
+impl Default for SensesConfig {
+    fn default() -> Self {
+        Self {
+            proprioception: false,
+            smell: false,
+            hearing: false,
+            smell_range: SMELL_RANGE,
+            hearing_range: HEARING_RANGE,
+        }
+    }
+}
+
+impl Default for FlockingConfig {
+    fn default() -> Self {
+        Self {
+            evolvable: false,
+            evolvable_radius: false,
+            coherence: COHERENCE,
+            separation: SEPARATION,
+            alignment: ALIGNMENT,
+            separation_radius: SEPARATION_RADIUS,
+            max_weight: MAX_FLOCKING_WEIGHT,
+            max_separation_radius: MAX_SEPARATION_RADIUS,
+        }
+    }
+}
+
+impl Default for PlasticityConfig {
+    fn default() -> Self {
+        Self {
+            mode: PlasticityMode::default(),
+            inheritance: Inheritance::default(),
+            max_learning_rate: MAX_LEARNING_RATE,
+            reward: REWARD,
+            trace_decay: TRACE_DECAY,
+        }
+    }
+}
+
 impl Default for EyeConfig {
     fn default() -> Self {
         Self {
             fov_range: FOV_RANGE,
             fov_angle: FOV_ANGLE,
             cells: CELLS,
+            animals: false,
+            obstacles: false,
+            mode: EyeMode::default(),
         }
     }
 }
@@ -109,6 +724,7 @@ impl Default for SimulationConfig {
             rotation_accel: ROTATION_ACCEL,
             mutation_chance: MUTATION_CHANCE,
             mutation_weight: MUTATION_WEIGHT,
+            crossover: CrossoverKind::default(),
             max_generation: MAX_GENERATION,
         }
     }
@@ -116,6 +732,22 @@ impl Default for SimulationConfig {
 
 #[cfg(test)]
 mod test {
+    use super::*;
+
     #[test]
     fn test_default_toml() {}
+
+    #[test]
+    #[should_panic(expected = "got more than one optimiser enabled")]
+    fn test_conflicting_optimisers() {
+        Config::parse_config("[multi_objective]\nenabled = true\n[differential]\nenabled = true");
+    }
+
+    #[test]
+    #[should_panic(expected = "got island overrides without genetic algorithms")]
+    fn test_overrides_without_ga() {
+        Config::parse_config(
+            "[cma_es]\nenabled = true\n[[islands.overrides]]\nmutation_chance = 0.0",
+        );
+    }
 }
@@ -0,0 +1,32 @@
+use crate::*;
+
+/// Crossover wraps every crossover method available from the configuration, like [`Selection`]
+/// does for selection methods.
+#[derive(Debug, Clone, Copy)]
+pub enum Crossover {
+    Uniform,
+    SinglePoint,
+}
+
+impl Crossover {
+    pub fn config_new(settings: Config) -> Self {
+        match settings.simulation.crossover {
+            CrossoverKind::Uniform => Self::Uniform,
+            CrossoverKind::SinglePoint => Self::SinglePoint,
+        }
+    }
+}
+
+impl ga::CrossoverMethod for Crossover {
+    fn crossover(
+        &self,
+        rng: &mut dyn RngCore,
+        parent_a: &ga::Chromosome,
+        parent_b: &ga::Chromosome,
+    ) -> ga::Chromosome {
+        match self {
+            Self::Uniform => ga::UniformCrossover.crossover(rng, parent_a, parent_b),
+            Self::SinglePoint => ga::SinglePointCrossover.crossover(rng, parent_a, parent_b),
+        }
+    }
+}
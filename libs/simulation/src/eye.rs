@@ -1,19 +1,53 @@
 use crate::*;
 
-use std::{f32::consts::PI, usize};
+use std::f32::consts::PI;
+
+// Radius within which a ray hits a food or an animal, as close as eating.
+const HIT_RADIUS: f32 = 0.01;
+
+/// Channel is what a cell array of the eye senses, every channel having its own cells.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Channel {
+    Food,
+    Animals,
+    Obstacles,
+}
+
+impl Channel {
+    // Name of the channel's cells in rendered brains.
+    fn label(&self) -> &'static str {
+        match self {
+            Self::Food => "eye",
+            Self::Animals => "flock",
+            Self::Obstacles => "obstacle",
+        }
+    }
+}
 
 #[derive(Debug)]
 pub struct Eye {
     fov_range: f32,
     fov_angle: f32,
     cells: usize,
+    channels: Vec<Channel>, // Concatenated in order in the vision, food first
+    mode: EyeMode,
 }
 
 impl Eye {
     pub fn config_new(settings: Config) -> Self {
         let config = settings.eye;
-        Self::new(&config.fov_range, &config.fov_angle, &config.cells)
+        let mut eye =
+            Self::new(&config.fov_range, &config.fov_angle, &config.cells).with_mode(config.mode);
+        if config.animals {
+            eye = eye.with_channel(Channel::Animals);
+        }
+        if config.obstacles {
+            eye = eye.with_channel(Channel::Obstacles);
+        }
+        eye
     }
+
+    /// Creates an eye sensing food only.
     pub fn new(fov_range: &f32, fov_angle: &f32, cells: &usize) -> Self {
         assert!(*fov_range > 0.0);
         assert!(*fov_angle > 0.0);
@@ -23,29 +57,173 @@ impl Eye {
             fov_range: *fov_range,
             fov_angle: *fov_angle,
             cells: *cells,
+            channels: vec![Channel::Food],
+            mode: EyeMode::Cells,
+        }
+    }
+
+    pub fn with_mode(mut self, mode: EyeMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Adds a cell array sensing given channel, after the existing ones.
+    pub fn with_channel(mut self, channel: Channel) -> Self {
+        if !self.channels.contains(&channel) {
+            self.channels.push(channel);
+        }
+        self
+    }
+
+    /// Replaces the field of view, keeping the channels.
+    pub(crate) fn with_fov(self, fov_range: f32, fov_angle: f32) -> Self {
+        let eye = Self::new(&fov_range, &fov_angle, &self.cells);
+        Self {
+            channels: self.channels,
+            mode: self.mode,
+            ..eye
         }
     }
 
+    pub fn fov_range(&self) -> f32 {
+        self.fov_range
+    }
+
+    pub fn fov_angle(&self) -> f32 {
+        self.fov_angle
+    }
+
+    /// Returns the number of cells of a single channel.
     pub fn cells(&self) -> usize {
         self.cells
     }
 
+    pub fn channels(&self) -> &[Channel] {
+        &self.channels
+    }
+
+    pub fn mode(&self) -> EyeMode {
+        self.mode
+    }
+
+    /// Returns the number of cells over every channel, as many as the brain's inputs.
+    pub fn inputs(&self) -> usize {
+        self.cells * self.channels.len()
+    }
+
+    /// Returns the name of every cell over every channel.
+    pub fn labels(&self) -> Vec<String> {
+        self.channels
+            .iter()
+            .flat_map(|channel| {
+                (0..self.cells).map(move |cell| format!("{}[{cell}]", channel.label()))
+            })
+            .collect()
+    }
+
+    /// Senses the foods, the other animals, given by their positions without the animal's own,
+    /// and the obstacles, concatenating the cells of every channel.
     pub fn calc_vision(
         &self,
         position: na::Point2<f32>,
         rotation: na::Rotation2<f32>,
         foods: &[Food],
+        animals: &[na::Point2<f32>],
+        obstacles: &[Obstacle],
+    ) -> Vec<f32> {
+        if self.mode == EyeMode::Raycast {
+            // Only the channel of the object hit by a ray gets the closeness in its cell.
+            let hits = self.cast_rays(position, rotation, foods, animals, obstacles);
+            let mut vision = vec![0.0; self.inputs()];
+            for (cell, hit) in hits.into_iter().enumerate() {
+                // Objects without a channel still block the sight, but are not seen.
+                let Some((channel, energy)) = hit else {
+                    continue;
+                };
+                if let Some(index) = self.channels.iter().position(|c| *c == channel) {
+                    vision[index * self.cells + cell] = energy;
+                }
+            }
+            return vision;
+        }
+
+        self.channels
+            .iter()
+            .flat_map(|channel| match channel {
+                Channel::Food => {
+                    let targets = foods.iter().map(Food::position);
+                    self.calc_cells(position, rotation, targets)
+                }
+                Channel::Animals => self.calc_cells(position, rotation, animals.iter().copied()),
+                // Obstacles have no single position to bin, rays tell how close they are.
+                Channel::Obstacles => self
+                    .cast_rays(position, rotation, &[], &[], obstacles)
+                    .into_iter()
+                    .map(|hit| hit.map_or(0.0, |(_, energy)| energy))
+                    .collect(),
+            })
+            .collect()
+    }
+
+    /// Casts one ray per cell, from the rightmost to the leftmost, returning the kind and the
+    /// closeness of the nearest object each ray hits in range.
+    fn cast_rays(
+        &self,
+        position: na::Point2<f32>,
+        rotation: na::Rotation2<f32>,
+        foods: &[Food],
+        animals: &[na::Point2<f32>],
+        obstacles: &[Obstacle],
+    ) -> Vec<Option<(Channel, f32)>> {
+        (0..self.cells)
+            .map(|cell| {
+                // Centre of the cell, relative to the boid's rotation.
+                let angle = ((cell as f32 + 0.5) / self.cells as f32 - 0.5) * self.fov_angle;
+                let direction = na::Rotation2::new(rotation.angle() + angle) * na::Vector2::y();
+
+                let foods = foods.iter().map(|food| {
+                    let hit = ray_circle(position, direction, food.position(), HIT_RADIUS);
+                    (Channel::Food, hit)
+                });
+                let animals = animals.iter().map(|other| {
+                    let hit = ray_circle(position, direction, *other, HIT_RADIUS);
+                    (Channel::Animals, hit)
+                });
+                let obstacles = obstacles
+                    .iter()
+                    .map(|obstacle| (Channel::Obstacles, obstacle.ray_hit(position, direction)));
+
+                let nearest = foods
+                    .chain(animals)
+                    .chain(obstacles)
+                    .filter_map(|(channel, hit)| Some((channel, hit?)))
+                    .filter(|(_, distance)| *distance < self.fov_range)
+                    .min_by(|(_, a), (_, b)| a.total_cmp(b));
+
+                // Inverse proportional distance over FOV's range as cell's energy
+                nearest.map(|(channel, distance)| {
+                    (channel, (self.fov_range - distance) / self.fov_range)
+                })
+            })
+            .collect()
+    }
+
+    fn calc_cells(
+        &self,
+        position: na::Point2<f32>,
+        rotation: na::Rotation2<f32>,
+        targets: impl Iterator<Item = na::Point2<f32>>,
     ) -> Vec<f32> {
         let mut cells = vec![0.0; self.cells];
 
-        for food in foods {
-            let vec = food.position() - position;
+        for target in targets {
+            let vec = target - position;
             let distance = vec.norm();
             if distance >= self.fov_range {
                 continue; // Out of range
             }
 
-            // Gets angle of food relative to y-axis.
+            // Gets angle of target relative to y-axis.
             let angle = na::Rotation2::rotation_between(&na::Vector2::y(), &vec).angle();
             let angle = angle - rotation.angle(); // Applying relative to boid's rotation.
             let angle = na::wrap(angle, -PI, PI); // TODO: Try to remove this.
@@ -116,6 +294,8 @@ mod test {
                 na::Point2::new(self.x, self.y),
                 na::Rotation2::new(self.rotation),
                 &self.foods,
+                &[],
+                &[],
             );
 
             let actual_vision: Vec<&'static str> = actual_vision
@@ -137,6 +317,113 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_channels() {
+        let eye = Eye::new(&1.0, &(2.0 * PI), &TEST_EYE_CELLS).with_channel(Channel::Animals);
+        let position = na::Point2::new(0.5, 0.5);
+        let animals = [na::Point2::new(0.5, 0.9)];
+
+        let vision = eye.calc_vision(position, na::Rotation2::new(0.0), &[], &animals, &[]);
+
+        assert_eq!(eye.inputs(), 2 * TEST_EYE_CELLS);
+        assert_eq!(vision.len(), eye.inputs());
+        // Nothing in the food channel, only the other animal in front of us.
+        assert!(vision[..TEST_EYE_CELLS].iter().all(|cell| *cell == 0.0));
+        let seen: Vec<f32> = vision[TEST_EYE_CELLS..]
+            .iter()
+            .copied()
+            .filter(|cell| *cell > 0.0)
+            .collect();
+        assert_eq!(seen.len(), 1);
+        assert!((seen[0] - 0.6).abs() < 1e-6);
+    }
+
+    // Renders every ray as the kind of object hit, in capital letters when it's close.
+    fn raycast(
+        foods: &[(f32, f32)],
+        animals: &[(f32, f32)],
+        obstacles: &[(f32, f32, f32)],
+        fov_range: f32,
+    ) -> String {
+        let eye = Eye::new(&fov_range, &FRAC_PI_2, &5)
+            .with_mode(EyeMode::Raycast)
+            .with_channel(Channel::Animals)
+            .with_channel(Channel::Obstacles);
+        let foods: Vec<Food> = foods.iter().map(|(x, y)| food(*x, *y)).collect();
+        let animals: Vec<na::Point2<f32>> = animals
+            .iter()
+            .map(|(x, y)| na::Point2::new(*x, *y))
+            .collect();
+        let obstacles: Vec<Obstacle> = obstacles
+            .iter()
+            .map(|(x, y, radius)| Obstacle::circle(na::Point2::new(*x, *y), *radius))
+            .collect();
+
+        let position = na::Point2::new(0.5, 0.5);
+        let vision = eye.calc_vision(
+            position,
+            na::Rotation2::new(0.0),
+            &foods,
+            &animals,
+            &obstacles,
+        );
+
+        (0..eye.cells())
+            .map(|cell| {
+                let hits: Vec<(char, f32)> = ['f', 'a', 'o']
+                    .into_iter()
+                    .enumerate()
+                    .map(|(channel, kind)| (kind, vision[channel * eye.cells() + cell]))
+                    .filter(|(_, energy)| *energy > 0.0)
+                    .collect();
+                assert!(hits.len() <= 1, "a ray hit more than one object");
+                match hits.first() {
+                    Some((kind, energy)) if *energy >= 0.5 => kind.to_ascii_uppercase(),
+                    Some((kind, _)) => *kind,
+                    None => ' ',
+                }
+            })
+            .collect()
+    }
+
+    #[test_case(&[(0.5, 0.7), (0.5, 0.9)], &[], &[], "  F  ")] // The nearer food hides the other
+    #[test_case(&[(0.5, 1.2)], &[], &[], "  f  ")] // Far food is dim
+    #[test_case(&[(0.5, 0.9)], &[], &[(0.5, 0.7, 0.05)], "  O  ")] // Obstacle hides the food
+    #[test_case(&[(0.5, 0.6)], &[], &[(0.5, 0.8, 0.05)], "  F  ")] // Food before the obstacle
+    #[test_case(&[], &[(0.6763, 0.7427)], &[], "A    ")] // Animal on our rightmost ray
+    #[test_case(&[(0.5, 0.6)], &[(0.5, 0.5)], &[], "AAAAA")] // Another animal right on us
+    #[test_case(&[(0.5, 0.7)], &[], &[(0.5, 0.9, 0.35)], "OOOOO")] // Wall of an obstacle
+    #[test_case(&[(0.7, 0.5), (0.5, 0.3)], &[], &[], "     ")] // Nothing between the rays
+    fn test_raycast(
+        foods: &[(f32, f32)],
+        animals: &[(f32, f32)],
+        obstacles: &[(f32, f32, f32)],
+        expected_vision: &'static str,
+    ) {
+        assert_eq!(raycast(foods, animals, obstacles, 1.0), expected_vision);
+    }
+
+    #[test_case(1.0, "  F  ")]
+    #[test_case(0.5, "  f  ")]
+    #[test_case(0.25, "     ")] // Food is out of range
+    fn test_raycast_range(fov_range: f32, expected_vision: &'static str) {
+        assert_eq!(raycast(&[(0.5, 0.8)], &[], &[], fov_range), expected_vision);
+    }
+
+    #[test]
+    fn test_raycast_occlusion_without_channel() {
+        let eye = Eye::new(&1.0, &FRAC_PI_2, &5).with_mode(EyeMode::Raycast);
+        let position = na::Point2::new(0.5, 0.5);
+        let foods = [food(0.5, 0.9)];
+        let animals = [na::Point2::new(0.5, 0.7)];
+
+        // The animal in front blocks the sight, though the eye doesn't see animals.
+        let vision = eye.calc_vision(position, na::Rotation2::new(0.0), &foods, &animals, &[]);
+        assert_eq!(vision, vec![0.0; 5]);
+        let vision = eye.calc_vision(position, na::Rotation2::new(0.0), &foods, &[], &[]);
+        assert!(vision[2] > 0.0);
+    }
+
     #[test_case(0.00 * PI, "         +   ")] // Food is to our right
     #[test_case(0.25 * PI, "        +    ")]
     #[test_case(0.50 * PI, "      +      ")] // Food is in front of us
@@ -155,8 +442,8 @@ mod test {
             y: 0.5,
             fov_range: 1.0,
             fov_angle: 2.0 * PI,
-            rotation: rotation,
-            expected_vision: expected_vision,
+            rotation,
+            expected_vision,
         }
         .run()
     }
@@ -193,7 +480,7 @@ mod test {
             fov_range: 1.0,
             fov_angle: FRAC_PI_2,
             rotation: 3.0 * FRAC_PI_2,
-            expected_vision: expected_vision,
+            expected_vision,
         }
         .run()
     }
@@ -215,8 +502,8 @@ mod test {
             y: 0.5,
             rotation: 0.0,
             fov_angle: FRAC_PI_2,
-            fov_range: fov_range,
-            expected_vision: expected_vision,
+            fov_range,
+            expected_vision,
         }
         .run()
     }
@@ -245,8 +532,8 @@ mod test {
             y: 0.5,
             fov_range: 1.0,
             rotation: 3.0 * FRAC_PI_2,
-            fov_angle: fov_angle,
-            expected_vision: expected_vision,
+            fov_angle,
+            expected_vision,
         }
         .run()
     }
@@ -0,0 +1,102 @@
+use crate::*;
+
+/// Flocking represents how strongly an animal follows each boid rule, and how close others may
+/// come before it moves away.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Flocking {
+    pub coherence: f32,
+    pub separation: f32,
+    pub alignment: f32,
+    pub separation_radius: f32,
+}
+
+impl Flocking {
+    pub fn config_new(settings: Config) -> Self {
+        let config = settings.flocking;
+
+        Self {
+            coherence: config.coherence,
+            separation: config.separation,
+            alignment: config.alignment,
+            separation_radius: config.separation_radius,
+        }
+    }
+
+    /// Decodes the weights from the next genes when they are evolvable, taking the configured
+    /// values otherwise.
+    pub(crate) fn from_genes(genes: &mut impl Iterator<Item = f32>, settings: Config) -> Self {
+        let config = settings.flocking;
+        let mut flocking = Self::config_new(settings);
+        if !config.evolvable {
+            return flocking;
+        }
+
+        let mut decode = |name: &str, max: f32| {
+            let gene = genes
+                .next()
+                .unwrap_or_else(|| panic!("got no {name} gene!"));
+            decode_gene(gene, 0.0, max)
+        };
+        flocking.coherence = decode("coherence", config.max_weight);
+        flocking.separation = decode("separation", config.max_weight);
+        flocking.alignment = decode("alignment", config.max_weight);
+        if config.evolvable_radius {
+            flocking.separation_radius = decode("separation radius", config.max_separation_radius);
+        }
+        flocking
+    }
+
+    /// Returns the number of genes encoding the weights.
+    pub fn count_genes(settings: Config) -> usize {
+        match (
+            settings.flocking.evolvable,
+            settings.flocking.evolvable_radius,
+        ) {
+            (false, _) => 0,
+            (true, false) => 3,
+            (true, true) => 4,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_from_genes() {
+        let settings = Config::parse_config(
+            "
+            [flocking]
+            evolvable = true
+            evolvable_radius = true
+            max_weight = 2.0
+            max_separation_radius = 0.1
+            ",
+        );
+        let mut genes = vec![0.0, 100.0, -100.0, 0.0, 7.0].into_iter();
+
+        let flocking = Flocking::from_genes(&mut genes, settings);
+
+        assert_eq!(Flocking::count_genes(settings), 4);
+        assert_eq!(flocking.coherence, 1.0);
+        assert!((flocking.separation - 2.0).abs() < 1e-6);
+        assert!(flocking.alignment.abs() < 1e-6);
+        assert!((flocking.separation_radius - 0.05).abs() < 1e-6);
+        // Only the flocking genes are taken.
+        assert_eq!(genes.next(), Some(7.0));
+    }
+
+    #[test]
+    fn test_fixed_weights() {
+        let settings = Config::parse_config("");
+        let mut genes = vec![1.0].into_iter();
+
+        let flocking = Flocking::from_genes(&mut genes, settings);
+
+        assert_eq!(Flocking::count_genes(settings), 0);
+        assert_eq!(flocking, Flocking::config_new(settings));
+        assert_eq!(flocking.separation, 0.55);
+        assert_eq!(genes.next(), Some(1.0));
+    }
+}
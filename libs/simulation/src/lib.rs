@@ -1,66 +1,328 @@
 use nalgebra::{self as na};
-use rand::{Rng, RngCore};
+use rand::{Rng, RngCore, SeedableRng, seq::SliceRandom};
+use rand_chacha::ChaCha8Rng;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
 use std::f32::consts::FRAC_PI_4;
 
 mod animal;
 mod animal_individual;
+mod benchmark;
 mod brain;
+mod champion;
 mod config;
+mod crossover;
 mod eye;
+mod flocking;
 mod food;
+mod noise;
+mod observer;
+mod obstacle;
+mod policy;
+mod rollout;
+mod run;
+mod selection;
+mod sense;
 mod swarm;
+mod trail;
 mod world;
 
 pub use self::{
-    animal::*, animal_individual::*, brain::*, config::*, eye::*, food::*, swarm::*, world::*,
+    animal::*, animal_individual::*, benchmark::*, brain::*, champion::*, config::*, crossover::*,
+    eye::*, flocking::*, food::*, noise::*, observer::*, obstacle::*, policy::*, rollout::*,
+    run::*, selection::*, sense::*, swarm::*, trail::*, world::*,
 };
 
 use lib_genetic_algorithm as ga;
 use lib_neural_network as nn;
 
 pub struct Simulation {
-    world: World,
-    ga: ga::GeneticAlgorithm<ga::RouletteWheelSelection>,
+    // Every island evolves in its own world, the first one being the one on display.
+    worlds: Vec<World>,
+    islands: ga::IslandModel<Selection>,
+    island_stats: Vec<ga::Statistics>,
+    speciation: Option<ga::Speciation>, // Only tracked under species selection
+    novelty: ga::NoveltySearch,
+    search: SearchMode,
+    map_elites: Option<ga::MapElites>,
+    nsga: Vec<ga::Nsga2>, // One per island, empty unless multi-objective
+    pareto_front: Vec<ga::ParetoPoint>,
+    cma_es: Vec<ga::CmaEs>, // One per island, empty unless enabled
+    differential: Vec<ga::DifferentialEvolution>, // One per island, empty unless enabled
+    rollouts: Rollouts,
     age: usize,
     global_best_fitness: f32,
     global_best_position: na::Point2<f32>,
     max_fitness: f32,
     fitness_std: f32,
     max_position: na::Point2<f32>,
+    max_chromosome: Option<ga::Chromosome>,
+    observers: Vec<Box<dyn Observer>>,
     config: SimulationConfig,
 }
 
 impl Simulation {
     pub fn random(rng: &mut dyn RngCore, settings: Config) -> Self {
         let config = settings.simulation;
-        let world = World::random(rng, settings);
-        let ga = ga::GeneticAlgorithm::new(
-            ga::RouletteWheelSelection,
-            ga::UniformCrossover,
-            ga::GaussianMutation::new(config.mutation_chance, config.mutation_weight),
+        let island_config = settings.islands;
+        assert!(island_config.count > 0);
+        let speciation = Self::speciation(settings);
+        let novelty_config = settings.novelty;
+        let fitness_weight = match novelty_config.mode {
+            SearchMode::Fitness => 1.0,
+            SearchMode::Novelty => 0.0,
+            SearchMode::Combined => novelty_config.fitness_weight,
+        };
+        let novelty = ga::NoveltySearch::new(
+            novelty_config.neighbours,
+            novelty_config.archive_threshold,
+            novelty_config.archive_capacity,
+            fitness_weight,
+        );
+
+        let mut worlds: Vec<World> = (0..island_config.count)
+            .map(|_| World::random(rng, settings))
+            .collect();
+        // Every island breeds with its overridden operators, islands past the overrides keeping
+        // the shared ones.
+        let islands = (0..island_config.count)
+            .map(|index| Self::island_new(island_config.overrides.get(index).apply(settings)))
+            .collect();
+        let nsga = if settings.multi_objective.enabled {
+            (0..island_config.count)
+                .map(|_| {
+                    ga::Nsga2::new(
+                        Crossover::config_new(settings),
+                        ga::GaussianMutation::new(config.mutation_chance, config.mutation_weight),
+                    )
+                })
+                .collect()
+        } else {
+            Vec::new()
+        };
+        // Centering each island's distribution on its initial random population, which is then
+        // replaced by the first samples of the distribution.
+        let cma_es = if settings.cma_es.enabled {
+            assert!(
+                settings.world.num_animals >= 2,
+                "got too few animals for CMA-ES"
+            );
+            worlds
+                .iter_mut()
+                .map(|world| {
+                    let chromosomes: Vec<_> =
+                        world.animals.iter().map(Animal::as_chromosome).collect();
+                    let mean = (0..chromosomes[0].len())
+                        .map(|gene| {
+                            chromosomes.iter().map(|c| c[gene]).sum::<f32>()
+                                / chromosomes.len() as f32
+                        })
+                        .collect();
+                    let cma_es = ga::CmaEs::new(&mean, settings.cma_es.sigma, chromosomes.len());
+                    world.animals = cma_es
+                        .ask(rng)
+                        .into_iter()
+                        .map(|chromosome| Animal::from_chromosome(rng, settings, chromosome))
+                        .collect();
+                    cma_es
+                })
+                .collect()
+        } else {
+            Vec::new()
+        };
+        let differential_config = settings.differential;
+        let differential = if differential_config.enabled {
+            (0..island_config.count)
+                .map(|_| {
+                    ga::DifferentialEvolution::new(
+                        differential_config.variant.into(),
+                        differential_config.f,
+                        differential_config.cr,
+                    )
+                })
+                .collect()
+        } else {
+            Vec::new()
+        };
+        let islands = ga::IslandModel::new(
+            islands,
+            island_config.topology.into(),
+            island_config.migration_interval,
+            island_config.migrants,
         );
 
         Self {
-            world,
-            ga,
+            worlds,
+            islands,
+            island_stats: Vec::new(),
+            speciation,
+            novelty,
+            search: novelty_config.mode,
+            map_elites: Self::map_elites_new(settings),
+            nsga,
+            pareto_front: Vec::new(),
+            cma_es,
+            differential,
+            rollouts: Rollouts::config_new(settings),
             age: 0,
             global_best_fitness: 0.0,
             global_best_position: na::Point2::from([0.0, 0.0]),
             max_fitness: 0.001, // Avoiding division by zero in inertia calculation
             fitness_std: 0.001,
             max_position: na::Point2::from([0.0, 0.0]),
+            max_chromosome: None,
+            observers: Vec::new(),
             config,
         }
     }
 
+    /// Places the obstacles of the map in every world, including the ones of rollouts.
+    pub fn set_map(&mut self, rng: &mut dyn RngCore, map: &Map) {
+        for world in &mut self.worlds {
+            world.set_obstacles(rng, map.obstacles.clone());
+        }
+        self.rollouts = self.rollouts.clone().with_map(map);
+    }
+
+    fn island_new(settings: Config) -> ga::GeneticAlgorithm<Selection> {
+        let config = settings.simulation;
+        let ga = ga::GeneticAlgorithm::new(
+            Selection::config_new(settings),
+            Crossover::config_new(settings),
+            ga::GaussianMutation::new(config.mutation_chance, config.mutation_weight),
+        );
+        match Self::speciation(settings) {
+            Some(speciation) => ga.with_speciation(speciation),
+            None => ga,
+        }
+    }
+
+    // Species are only worth clustering for when they drive the selection.
+    fn speciation(settings: Config) -> Option<ga::Speciation> {
+        (settings.selection.method == SelectionKind::Species)
+            .then(|| ga::Speciation::new(settings.selection.species_threshold))
+    }
+
+    /// Registers an observer notified along the steps and generations.
+    pub fn add_observer(&mut self, observer: impl Observer + 'static) {
+        self.observers.push(Box::new(observer));
+    }
+
+    /// Returns the world of the first island, the one on display.
     pub fn world(&self) -> &World {
-        &self.world
+        &self.worlds[0]
+    }
+
+    /// Returns the worlds of every island.
+    pub fn worlds(&self) -> &[World] {
+        &self.worlds
+    }
+
+    /// Returns the statistics of every island for the last evolved generation.
+    pub fn island_statistics(&self) -> &[ga::Statistics] {
+        &self.island_stats
+    }
+
+    /// Returns the novelty search, holding the archive of novel behaviours.
+    pub fn novelty(&self) -> &ga::NoveltySearch {
+        &self.novelty
+    }
+
+    /// Returns the Pareto front over food eaten, energy spent and flock cohesion for the last
+    /// evolved generation, empty unless multi-objective evolution is enabled.
+    pub fn pareto_front(&self) -> &[ga::ParetoPoint] {
+        &self.pareto_front
+    }
+
+    /// Returns the MAP-Elites archive over average speed and turning rate, if enabled.
+    pub fn map_elites(&self) -> Option<&ga::MapElites> {
+        self.map_elites.as_ref()
+    }
+
+    /// Replaces the animals of the first world with copies of the elite at given MAP-Elites
+    /// coordinates, restarting the generation to watch it. Returns false if the cell is empty.
+    pub fn load_elite(
+        &mut self,
+        rng: &mut dyn RngCore,
+        settings: Config,
+        coordinates: &[usize],
+    ) -> bool {
+        let Some(elite) = self
+            .map_elites()
+            .and_then(|archive| archive.get(coordinates))
+        else {
+            return false;
+        };
+        let chromosome = elite.chromosome.clone();
+
+        let world = &mut self.worlds[0];
+        for animal in &mut world.animals {
+            *animal = Animal::from_chromosome(rng, settings, chromosome.clone());
+        }
+        self.age = 0;
+        true
+    }
+
+    /// Returns the chromosome of the fittest animal of the last evaluated generation, if any.
+    pub fn generation_best(&self) -> Option<&ga::Chromosome> {
+        self.max_chromosome.as_ref()
+    }
+
+    /// Returns the fittest animal of the last evaluated generation as a champion to be saved, if
+    /// any generation was evaluated.
+    pub fn champion(&self, name: &str, settings: Config) -> Option<Champion> {
+        self.max_chromosome
+            .as_ref()
+            .map(|chromosome| Champion::new(name, self.max_fitness, chromosome, settings))
+    }
+
+    /// Returns the genetic diversity over the animals of every island.
+    pub fn diversity(&self) -> f32 {
+        let population: Vec<AnimalIndividual> = self
+            .worlds
+            .iter()
+            .flat_map(|world| world.animals.iter())
+            .map(AnimalIndividual::from_animal)
+            .collect();
+        ga::diversity(&population)
+    }
+
+    /// Returns the activations of every layer in the animal's brain for what it currently sees,
+    /// or `None` if there is no animal at given index.
+    pub fn trace_brain(&self, index: usize) -> Option<Vec<Vec<f32>>> {
+        let world = self.world();
+        let animal = world.animals.get(index)?;
+        let vision = animal.perceive(
+            index,
+            &Surroundings::new(&world.foods, &world.obstacles, &world.animals),
+        );
+        Some(animal.brain.nn.propagate_traced(vision))
     }
 
     pub fn step(&mut self, rng: &mut dyn RngCore, settings: Config) -> Option<ga::Statistics> {
-        self.calc_movement();
-        self.calc_brain();
-        self.calc_collision(rng);
+        // Every world steps with its own rng seeded from the given one, so that stepping them
+        // concurrently stays deterministic.
+        let seeds: Vec<u64> = self.worlds.iter().map(|_| rng.r#gen()).collect();
+        let config = &self.config;
+        #[cfg(feature = "parallel")]
+        let worlds = self.worlds.par_iter_mut();
+        #[cfg(not(feature = "parallel"))]
+        let worlds = self.worlds.iter_mut();
+        let eaten: Vec<Vec<usize>> = worlds
+            .zip(seeds)
+            .map(|(world, seed)| {
+                Self::step_world(world, config, &mut ChaCha8Rng::seed_from_u64(seed))
+            })
+            .collect();
+
+        for (island, (world, eaten)) in self.worlds.iter().zip(eaten).enumerate() {
+            for observer in &mut self.observers {
+                for &animal in &eaten {
+                    observer.on_food_eaten(island, world, &world.animals[animal]);
+                }
+                observer.on_step(island, world);
+            }
+        }
         self.age += 1;
         if self.age > self.config.max_generation {
             Some(self.evolve(rng, settings))
@@ -77,86 +339,330 @@ impl Simulation {
         }
     }
 
+    /// Evolves generation after generation until any stop condition of the `[run]` section is
+    /// met, returning a summary with the fittest chromosome.
+    pub fn run(&mut self, rng: &mut dyn RngCore, settings: Config) -> RunSummary {
+        RunController::config_new(settings).run(self, rng, settings)
+    }
+
+    /// Plays the hand-written policy instead of the brains for given number of steps, recording
+    /// every animal's `(vision, action)` pairs. The world moves on as usual, but the food eaten
+    /// meanwhile doesn't count towards fitness.
+    pub fn record_demonstrations(
+        &mut self,
+        rng: &mut dyn RngCore,
+        policy: &SteeringPolicy,
+        steps: usize,
+    ) -> Vec<(Vec<f32>, Vec<f32>)> {
+        let mut samples = Vec::new();
+
+        for world in &mut self.worlds {
+            for _ in 0..steps {
+                Self::calc_movement(world);
+                let surroundings =
+                    Surroundings::new(&world.foods, &world.obstacles, &world.animals);
+                for (index, animal) in world.animals.iter_mut().enumerate() {
+                    let vision = animal.perceive(index, &surroundings);
+                    let action = policy.act(&animal.eye, &vision);
+                    animal.steer(&action, &self.config);
+                    samples.push((vision, action));
+                }
+                Self::calc_collision(world, rng);
+            }
+
+            for animal in &mut world.animals {
+                animal.hunger = 0;
+                animal.trail.reset();
+            }
+        }
+
+        samples
+    }
+
+    /// Pretrains every brain on recorded `(vision, action)` pairs with backpropagation, before
+    /// the genetic algorithm takes over. Each brain gets its own optimizer, as stateful ones like
+    /// Adam can't be shared between networks. Returns the mean loss over the last epoch.
+    pub fn pretrain(
+        &mut self,
+        rng: &mut dyn RngCore,
+        samples: &[(Vec<f32>, Vec<f32>)],
+        epochs: usize,
+        batch_size: usize,
+        mut optimizer: impl FnMut() -> Box<dyn nn::Optimizer>,
+    ) -> f32 {
+        let animals = self
+            .worlds
+            .iter_mut()
+            .flat_map(|world| world.animals.iter_mut());
+        let (count, losses) = animals.fold((0, 0.0), |(count, losses), animal| {
+            let loss =
+                animal
+                    .brain
+                    .pretrain(rng, optimizer().as_mut(), samples, epochs, batch_size);
+            (count + 1, losses + loss)
+        });
+
+        losses / count.max(1) as f32
+    }
+
     fn evolve(&mut self, rng: &mut dyn RngCore, settings: Config) -> ga::Statistics {
         self.age = 0;
 
-        // Gather the current population.
-        let current_population: Vec<AnimalIndividual> = self
-            .world
-            .animals
+        // Gather the current population of every island, evaluated over the rollouts if any
+        // instead of the displayed worlds.
+        let seeds = self.rollouts.seeds(rng);
+        let current_populations: Vec<Vec<AnimalIndividual>> = self
+            .worlds
             .iter()
-            .map(AnimalIndividual::from_animal)
+            .map(|world| {
+                let population = world.animals.iter().map(AnimalIndividual::from_animal);
+                if self.rollouts.is_disabled() {
+                    return population.collect();
+                }
+
+                let chromosomes: Vec<ga::Chromosome> =
+                    world.animals.iter().map(Animal::as_chromosome).collect();
+                let fitness = self.rollouts.evaluate(&seeds, settings, &chromosomes);
+                population
+                    .zip(fitness)
+                    .map(|(individual, fitness)| individual.with_fitness(fitness))
+                    .collect()
+            })
             .collect();
 
-        // Evolve the current population with genetic algorithms.
-        let (new_population, stats) = self.ga.evolve(rng, &current_population);
+        // Evolve the current populations with genetic algorithms, migrating between islands,
+        // unless MAP-Elites, NSGA-II, CMA-ES or differential evolution, at most one of them as
+        // checked by the settings, breed them instead.
+        let (new_populations, island_stats) = if self.map_elites.is_some() {
+            self.evolve_map_elites(rng, &current_populations)
+        } else if !self.nsga.is_empty() {
+            self.evolve_nsga(rng, &current_populations)
+        } else if !self.cma_es.is_empty() {
+            self.cma_es
+                .iter_mut()
+                .zip(&current_populations)
+                .map(|(cma_es, population)| cma_es.evolve(rng, population))
+                .unzip()
+        } else if !self.differential.is_empty() {
+            self.differential
+                .iter_mut()
+                .zip(&current_populations)
+                .map(|(differential, population)| differential.evolve(rng, population))
+                .unzip()
+        } else {
+            match self.search {
+                SearchMode::Fitness => self.islands.evolve(rng, &current_populations),
+                SearchMode::Novelty | SearchMode::Combined => {
+                    let scores: Vec<Vec<f32>> = current_populations
+                        .iter()
+                        .map(|population| self.novelty.scores(population))
+                        .collect();
+                    self.islands
+                        .evolve_with_scores(rng, &current_populations, &scores)
+                }
+            }
+        };
 
-        self.max_position = self.world.animals[stats.best_index].position();
+        // Statistics over every island, where the best index spans all populations in order.
+        let population = current_populations.concat();
+        let mut stats = ga::Statistics::new(&population);
+        if let Some(speciation) = &self.speciation {
+            stats.species_sizes = speciation.sizes(&population);
+        }
+        self.island_stats = island_stats;
+
+        let best = self
+            .worlds
+            .iter()
+            .flat_map(|world| world.animals.iter())
+            .nth(stats.best_index)
+            .expect("got best individual that doesn't exists");
+        self.max_position = best.position();
+        self.max_chromosome = Some(best.as_chromosome());
         self.max_fitness = stats.max_fitness;
         self.fitness_std = stats.fitness_std;
 
         if self.max_fitness > self.global_best_fitness {
             self.global_best_fitness = self.max_fitness;
             self.global_best_position = self.max_position;
+
+            let best = self
+                .max_chromosome
+                .as_ref()
+                .expect("got no best chromosome");
+            for observer in &mut self.observers {
+                observer.on_new_best(best, self.max_fitness);
+            }
         }
 
-        // Create our evolved population into the ecosystem.
-        self.world.animals = new_population
-            .into_iter()
-            .map(|individual| individual.into_animal(rng, settings))
-            .collect();
+        for observer in &mut self.observers {
+            for (island, world) in self.worlds.iter().enumerate() {
+                for animal in &world.animals {
+                    observer.on_death(island, world, animal);
+                }
+            }
+            observer.on_generation(&stats, &self.worlds);
+        }
+
+        for (world, new_population) in self.worlds.iter_mut().zip(new_populations) {
+            // Create our evolved population into the ecosystem.
+            world.animals = new_population
+                .into_iter()
+                .map(|individual| individual.into_animal(rng, settings))
+                .collect();
 
-        // Randomizing food after each evolution to be easily recognizable.
-        for food in &mut self.world.foods {
-            food.position = rng.r#gen();
+            // Randomizing food after each evolution to be easily recognizable.
+            for food in &mut world.foods {
+                food.position = free_position(rng, &world.obstacles);
+            }
+            world.free_overlaps(rng);
         }
 
         stats
     }
 
-    fn calc_brain(&mut self) {
-        for animal in &mut self.world.animals {
-            let speed_accel = self.config.speed_accel;
-            let rotation_accel = self.config.rotation_accel;
-            let speed_min = self.config.speed_min;
-            let speed_max = self.config.speed_max;
+    // Inserts every animal into the MAP-Elites archive, with the fitness of its population as
+    // evaluated over the rollouts if any, then breeds each island from its elites.
+    fn evolve_map_elites(
+        &mut self,
+        rng: &mut dyn RngCore,
+        populations: &[Vec<AnimalIndividual>],
+    ) -> (Vec<Vec<AnimalIndividual>>, Vec<ga::Statistics>) {
+        let archive = self.map_elites.as_mut().expect("got no MAP-Elites archive");
+        for (world, population) in self.worlds.iter().zip(populations) {
+            for (animal, individual) in world.animals.iter().zip(population) {
+                archive.insert(
+                    ga::Individual::chromosome(individual),
+                    ga::Individual::fitness(individual),
+                    &animal.motion(),
+                );
+            }
+        }
 
-            let vision =
-                animal
-                    .eye
-                    .calc_vision(animal.position, animal.rotation, &self.world.foods);
+        let mutation =
+            ga::GaussianMutation::new(self.config.mutation_chance, self.config.mutation_weight);
+        populations
+            .iter()
+            .map(|population| {
+                let offsprings = archive
+                    .breed(rng, &ga::UniformCrossover, &mutation, population.len())
+                    .into_iter()
+                    .map(ga::Individual::create)
+                    .collect();
+                (offsprings, ga::Statistics::new(population))
+            })
+            .unzip()
+    }
 
-            let output = animal.brain.nn.propagate(vision);
-            let speed = output[0].clamp(-speed_accel, speed_accel);
-            let angle = output[1].clamp(-rotation_accel, rotation_accel);
+    // Evolves each island with its own NSGA-II, keeping the Pareto front over all islands.
+    fn evolve_nsga(
+        &mut self,
+        rng: &mut dyn RngCore,
+        populations: &[Vec<AnimalIndividual>],
+    ) -> (Vec<Vec<AnimalIndividual>>, Vec<ga::Statistics>) {
+        let mut fronts = Vec::new();
+        let evolved = self
+            .nsga
+            .iter_mut()
+            .zip(populations)
+            .map(|(nsga, population)| {
+                let (offsprings, front) = nsga.evolve(rng, population);
+                fronts.extend(front);
+                (offsprings, ga::Statistics::new(population))
+            })
+            .unzip();
 
-            animal.speed = (animal.speed + speed).clamp(speed_min, speed_max);
-            animal.rotation = na::Rotation2::new(animal.rotation.angle() + angle);
-        }
+        let objectives: Vec<Vec<f32>> = fronts.iter().map(|p| p.objectives.clone()).collect();
+        let pareto_front = &ga::non_dominated_sort(&objectives)[0];
+        self.pareto_front = pareto_front.iter().map(|&i| fronts[i].clone()).collect();
+
+        evolved
     }
 
-    fn calc_movement(&mut self) {
-        let mut updates: Vec<na::Vector2<f32>> = Vec::new();
-        let coherence_weight = 0.1;
-        let separation_weight = 0.55;
-        let alignment_weight = 0.1;
+    fn map_elites_new(settings: Config) -> Option<ga::MapElites> {
+        let config = settings.map_elites;
+        let simulation = settings.simulation;
+        config.enabled.then(|| {
+            ga::MapElites::new(vec![
+                ga::Dimension::new(
+                    simulation.speed_min,
+                    simulation.speed_max,
+                    config.speed_bins,
+                ),
+                ga::Dimension::new(0.0, simulation.rotation_accel, config.turning_bins),
+            ])
+        })
+    }
+
+    // Moves, thinks and eats for one step, returning the index of the animal for every food eaten.
+    fn step_world(
+        world: &mut World,
+        config: &SimulationConfig,
+        rng: &mut dyn RngCore,
+    ) -> Vec<usize> {
+        Self::calc_movement(world);
+        Self::calc_brain(world, config, rng);
+        Self::calc_collision(world, rng)
+    }
+
+    fn calc_brain(world: &mut World, config: &SimulationConfig, rng: &mut dyn RngCore) {
+        let surroundings = Surroundings::new(&world.foods, &world.obstacles, &world.animals);
+        // Noisy animals draw from their own rng, seeded in order, so that they can still think
+        // concurrently and deterministically.
+        let seeds: Vec<Option<u64>> = world
+            .animals
+            .iter()
+            .map(|animal| (!animal.noise.is_disabled()).then(|| rng.r#gen()))
+            .collect();
+        #[cfg(feature = "parallel")]
+        let animals = world.animals.par_iter_mut().zip(seeds).enumerate();
+        #[cfg(not(feature = "parallel"))]
+        let animals = world.animals.iter_mut().zip(seeds).enumerate();
 
+        animals.for_each(|(index, (animal, seed))| {
+            let mut vision = animal.perceive(index, &surroundings);
+
+            let output = match seed {
+                None => animal.think(vision),
+                Some(seed) => {
+                    let rng = &mut ChaCha8Rng::seed_from_u64(seed);
+                    let cells = animal.eye.inputs();
+                    animal.noise.perturb_vision(rng, &mut vision[..cells]);
+                    let output = animal.think(vision);
+                    animal.noise.perturb_output(rng, output)
+                }
+            };
+            animal.steer(&output, config);
+        });
+    }
+
+    fn calc_movement(world: &mut World) {
         // Computing boid algorithm movement separately, following some reddit comment
-        // (should really reference it huh)
-        for animal in &self.world.animals {
-            let coherence = self.world.calc_coherence(&animal);
-            let separation = self.world.calc_separation(&animal);
-            let alignment = self.world.calc_alignment(&animal);
+        // (should really reference it huh). Every animal reads the world as it was before the
+        // step, so that they can be computed concurrently.
+        #[cfg(feature = "parallel")]
+        let animals = world.animals.par_iter();
+        #[cfg(not(feature = "parallel"))]
+        let animals = world.animals.iter();
+        let updates: Vec<(na::Vector2<f32>, f32)> = animals
+            .map(|animal| {
+                let coherence = world.calc_coherence(animal);
+                let separation = world.calc_separation(animal);
+                let alignment = world.calc_alignment(animal);
 
-            // We dont' want the animal to teleport to the flock center, etc.
-            // So we convert it to a direction vector for the animal to go towards it.
-            let delta = coherence.coords * coherence_weight
-                + separation.coords * separation_weight
-                + alignment.coords * alignment_weight;
-            updates.push(delta)
-        }
+                // We dont' want the animal to teleport to the flock center, etc.
+                // So we convert it to a direction vector for the animal to go towards it.
+                let weights = animal.flocking();
+                let delta = coherence.coords * weights.coherence
+                    + separation.coords * weights.separation
+                    + alignment.coords * weights.alignment;
+                // Coherence points at the flock's centre, its length telling how far the animal
+                // strays.
+                (delta, coherence.coords.magnitude())
+            })
+            .collect();
 
-        for (animal, delta) in self.world.animals.iter_mut().zip(updates) {
+        for (animal, (delta, spread)) in world.animals.iter_mut().zip(updates) {
             /*
             let inertia = Swarm::calc_inertia(self.max_fitness, self.fitness_std);
             let cognition = Swarm::calc_cognition(rng, self.max_position, animal.position());
@@ -174,19 +680,574 @@ impl Simulation {
             animal.position += velocity;
             animal.position.x = na::wrap(animal.position.x, 0.0, 1.0);
             animal.position.y = na::wrap(animal.position.y, 0.0, 1.0);
+
+            // Sliding along the obstacles bumped into, wrapping again if pushed over an edge.
+            let mut bumped = false;
+            for obstacle in &world.obstacles {
+                if let Some(position) = obstacle.push_out(animal.position, BODY_RADIUS) {
+                    animal.position = position;
+                    bumped = true;
+                }
+            }
+            if bumped {
+                animal.collisions += 1;
+                animal.position.x = na::wrap(animal.position.x, 0.0, 1.0);
+                animal.position.y = na::wrap(animal.position.y, 0.0, 1.0);
+            }
+            animal.trail.record(animal.position, animal.speed);
+            animal.trail.record_spread(spread);
         }
     }
 
-    fn calc_collision(&mut self, rng: &mut dyn RngCore) {
-        for animal in &mut self.world.animals {
+    // Returns the index of the animal for every food eaten.
+    fn calc_collision(world: &mut World, rng: &mut dyn RngCore) -> Vec<usize> {
+        let mut eaten = Vec::new();
+        for (index, animal) in world.animals.iter_mut().enumerate() {
+            animal.call *= CALL_DECAY;
             // Brute force implementation
-            for food in &mut self.world.foods {
+            for food in &mut world.foods {
                 let distance = na::distance(&animal.position, &food.position);
                 if distance <= 0.01 {
-                    animal.hunger += 1;
-                    food.position = rng.r#gen();
+                    animal.eat();
+                    food.position = free_position(rng, &world.obstacles);
+                    eaten.push(index);
                 }
             }
         }
+        eaten
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::{cell::RefCell, rc::Rc};
+    use test_case::test_case;
+
+    // Plenty of food over a short generation, so that roulette selection has fitness to spin on.
+    const TEST_CONFIG: &str = "
+        [simulation]
+        max_generation = 50
+
+        [world]
+        num_animals = 10
+        num_foods = 500
+    ";
+
+    #[test]
+    fn test_islands() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let settings = Config::parse_config(&format!(
+            "{TEST_CONFIG}\n[islands]\ncount = 3\nmigration_interval = 1\nmigrants = 2"
+        ));
+        let mut simulation = Simulation::random(&mut rng, settings);
+
+        let stats = simulation.fast_forward(&mut rng, settings);
+        let island_stats = simulation.island_statistics();
+
+        assert_eq!(simulation.worlds().len(), 3);
+        assert_eq!(island_stats.len(), 3);
+        assert!(simulation.worlds().iter().all(|w| w.animals().len() == 10));
+
+        let best = island_stats
+            .iter()
+            .map(|s| s.max_fitness)
+            .fold(0.0, f32::max);
+        assert_eq!(stats.max_fitness, best);
+    }
+
+    #[test]
+    fn test_island_overrides() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let contents = format!(
+            "{TEST_CONFIG}
+            [islands]
+            count = 2
+            migration_interval = 0
+
+            [[islands.overrides]]
+            mutation_chance = 0.0
+            crossover = \"single_point\"
+
+            [[islands.overrides]]
+            mutation_chance = 1.0
+            "
+        );
+        let settings = Config::parse_config(&contents);
+        let overrides = settings.islands.overrides;
+        assert_eq!(overrides.len(), 2);
+        assert_eq!(overrides.get(0).crossover, Some(CrossoverKind::SinglePoint));
+        assert_eq!(overrides.get(1).selection, None);
+        assert_eq!(overrides.get(2), IslandOverride::default());
+
+        let mut simulation = Simulation::random(&mut rng, settings);
+        let parents: Vec<Vec<_>> = simulation
+            .worlds()
+            .iter()
+            .map(|world| world.animals().iter().map(Animal::as_chromosome).collect())
+            .collect();
+        simulation.fast_forward(&mut rng, settings);
+
+        // Without mutation every gene comes from a parent, while every gene mutates otherwise.
+        let inherited = |island: usize| {
+            simulation.worlds()[island].animals().iter().all(|animal| {
+                animal
+                    .as_chromosome()
+                    .iter()
+                    .enumerate()
+                    .all(|(gene, value)| parents[island].iter().any(|p| p[gene] == *value))
+            })
+        };
+        assert!(inherited(0));
+        assert!(!inherited(1));
+    }
+
+    #[test]
+    fn test_inheritance_modes() {
+        // Counts the animals that would pass on other genes than the ones they were born with.
+        let run = |inheritance: &str| {
+            let mut rng = ChaCha8Rng::from_seed(Default::default());
+            let settings = Config::parse_config(&format!(
+                "{TEST_CONFIG}\n[plasticity]\nmode = \"hebbian\"\ninheritance = \"{inheritance}\""
+            ));
+            let mut simulation = Simulation::random(&mut rng, settings);
+            for _ in 0..20 {
+                simulation.step(&mut rng, settings);
+            }
+            simulation.worlds()[0]
+                .animals()
+                .iter()
+                .filter(|animal| !animal.as_chromosome().iter().eq(animal.genome.iter()))
+                .count()
+        };
+
+        // Same seed, only the learned weights written back set the two runs apart.
+        assert_eq!(run("baldwinian"), 0);
+        assert!(run("lamarckian") > 0);
+    }
+
+    #[test]
+    fn test_selection_without_fitness() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let settings = Config::parse_config(&format!(
+            "{TEST_CONFIG}\n[sensory]\nevolvable = true\nrange_cost = 100"
+        ));
+        let mut simulation = Simulation::random(&mut rng, settings);
+
+        // Sight costs more than any food brings, yet the fitness of animals that ate stays
+        // positive.
+        let stats = simulation.fast_forward(&mut rng, settings);
+
+        assert!(stats.max_fitness > 0.0);
+        assert!(stats.max_fitness < 1.0);
+        assert!(simulation.worlds().iter().all(|w| w.animals().len() == 10));
+    }
+
+    #[test]
+    fn test_species_selection() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let settings = Config::parse_config(&format!(
+            "{TEST_CONFIG}\n[selection]\nmethod = \"species\"\nspecies_threshold = 1.0"
+        ));
+        let mut simulation = Simulation::random(&mut rng, settings);
+
+        let stats = simulation.fast_forward(&mut rng, settings);
+
+        // Random brains are far apart, every animal starts as its own species.
+        assert!(stats.species_count() > 1);
+        assert_eq!(stats.species_sizes.iter().sum::<usize>(), 10);
+
+        // Other selections don't pay for clustering species.
+        let settings = Config::parse_config(TEST_CONFIG);
+        let mut simulation = Simulation::random(&mut rng, settings);
+        assert_eq!(
+            simulation.fast_forward(&mut rng, settings).species_count(),
+            0
+        );
+    }
+
+    #[test]
+    fn test_novelty_search() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let settings = Config::parse_config(&format!(
+            "{TEST_CONFIG}\n[novelty]\nmode = \"novelty\"\ndescriptor = \"trajectory\"\narchive_threshold = 0.0"
+        ));
+        let mut simulation = Simulation::random(&mut rng, settings);
+
+        simulation.fast_forward(&mut rng, settings);
+
+        // Every behaviour is novel enough with a zero threshold.
+        assert_eq!(simulation.novelty().archive().count(), 10);
+        assert!(
+            simulation
+                .novelty()
+                .archive()
+                .all(|behaviour| behaviour.features().len() == 16)
+        );
+    }
+
+    #[test]
+    fn test_map_elites() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let settings = Config::parse_config(&format!(
+            "{TEST_CONFIG}\n[map_elites]\nenabled = true\nspeed_bins = 4\nturning_bins = 4"
+        ));
+        let mut simulation = Simulation::random(&mut rng, settings);
+
+        simulation.fast_forward(&mut rng, settings);
+
+        let archive = simulation.map_elites().unwrap();
+        assert_eq!(archive.heatmap().len(), 16);
+        assert!(archive.coverage() > 0.0);
+
+        let elite = archive.elites().next().unwrap();
+        let coordinates = archive.coordinates(&elite.behaviour);
+        let chromosome = elite.chromosome.clone();
+        assert!(simulation.load_elite(&mut rng, settings, &coordinates));
+        assert!(
+            simulation
+                .world()
+                .animals()
+                .iter()
+                .all(|animal| animal.as_chromosome().iter().eq(chromosome.iter()))
+        );
+        assert!(!simulation.load_elite(&mut rng, settings, &[4, 0]));
+    }
+
+    #[test]
+    fn test_map_elites_rollouts() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let settings = Config::parse_config(&format!(
+            "{TEST_CONFIG}\n[map_elites]\nenabled = true\n[rollouts]\ncount = 2\naggregate = \"median\""
+        ));
+        let mut simulation = Simulation::random(&mut rng, settings);
+
+        let stats = simulation.fast_forward(&mut rng, settings);
+
+        // Elites are archived with the median of the rollouts, not the displayed world's fitness.
+        let archive = simulation.map_elites().unwrap();
+        assert!(
+            archive
+                .elites()
+                .all(|elite| (elite.fitness * 2.0).fract() == 0.0)
+        );
+        let best = archive
+            .elites()
+            .map(|elite| elite.fitness)
+            .fold(0.0, f32::max);
+        assert_eq!(best, stats.max_fitness);
+    }
+
+    #[test]
+    fn test_multi_objective() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let settings =
+            Config::parse_config(&format!("{TEST_CONFIG}\n[multi_objective]\nenabled = true"));
+        let mut simulation = Simulation::random(&mut rng, settings);
+
+        simulation.fast_forward(&mut rng, settings);
+
+        let front = simulation.pareto_front();
+        assert!(!front.is_empty());
+        for a in front {
+            assert_eq!(a.objectives.len(), 3);
+            assert!(
+                front
+                    .iter()
+                    .all(|b| !ga::dominates(&b.objectives, &a.objectives))
+            );
+        }
+    }
+
+    #[test]
+    fn test_cma_es() {
+        let settings = Config::parse_config(&format!("{TEST_CONFIG}\n[cma_es]\nenabled = true"));
+        let run = || {
+            let mut rng = ChaCha8Rng::from_seed(Default::default());
+            let mut simulation = Simulation::random(&mut rng, settings);
+            (0..2)
+                .map(|_| simulation.fast_forward(&mut rng, settings).max_fitness)
+                .collect::<Vec<_>>()
+        };
+
+        // Identical seeds give identical runs, to compare against the genetic algorithm.
+        assert_eq!(run(), run());
+    }
+
+    #[test]
+    fn test_cma_es_first_generation() {
+        let genes = |settings: Config| {
+            let mut rng = ChaCha8Rng::from_seed(Default::default());
+            let simulation = Simulation::random(&mut rng, settings);
+            simulation
+                .world()
+                .animals()
+                .iter()
+                .flat_map(|animal| animal.as_chromosome().iter().copied().collect::<Vec<_>>())
+                .collect::<Vec<_>>()
+        };
+        let sampled = Config::parse_config(&format!("{TEST_CONFIG}\n[cma_es]\nenabled = true"));
+
+        // The random animals only center the distribution, the first ones being sampled from it.
+        assert_ne!(genes(sampled), genes(Config::parse_config(TEST_CONFIG)));
+    }
+
+    #[test]
+    #[should_panic(expected = "got too few animals for CMA-ES")]
+    fn test_cma_es_without_animals() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let settings = Config::parse_config("[world]\nnum_animals = 0\n[cma_es]\nenabled = true");
+        Simulation::random(&mut rng, settings);
+    }
+
+    #[test]
+    fn test_differential_evolution() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let settings = Config::parse_config(&format!(
+            "{TEST_CONFIG}\n[differential]\nenabled = true\nvariant = \"best1_bin\"\nf = 0.7"
+        ));
+        let mut simulation = Simulation::random(&mut rng, settings);
+
+        simulation.fast_forward(&mut rng, settings);
+        let stats = simulation.fast_forward(&mut rng, settings);
+
+        assert_eq!(simulation.world().animals().len(), 10);
+        assert!(stats.max_fitness > 0.0);
+    }
+
+    #[test]
+    fn test_run_controller() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let settings = Config::parse_config(&format!("{TEST_CONFIG}\n[run]\ngenerations = 3"));
+        let mut simulation = Simulation::random(&mut rng, settings);
+
+        let summary = simulation.run(&mut rng, settings);
+
+        assert_eq!(summary.generations, 3);
+        assert_eq!(summary.stop_condition, StopCondition::MaxGenerations(3));
+        assert!(summary.best_fitness >= summary.statistics.max_fitness);
+        assert_eq!(
+            summary.best_chromosome.len(),
+            simulation.generation_best().unwrap().len()
+        );
+    }
+
+    #[test]
+    fn test_run_stop_conditions() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let settings = Config::parse_config(TEST_CONFIG);
+        let mut simulation = Simulation::random(&mut rng, settings);
+
+        // The first condition met ends the run, even when listed after others.
+        let controller = RunController::new()
+            .with(StopCondition::MaxGenerations(100))
+            .with(StopCondition::TargetFitness(1.0));
+        let summary = controller.run(&mut simulation, &mut rng, settings);
+        assert_eq!(summary.stop_condition, StopCondition::TargetFitness(1.0));
+        assert!(summary.best_fitness >= 1.0);
+
+        let controller = RunController::new()
+            .with(StopCondition::MaxGenerations(100))
+            .with(StopCondition::DiversityFloor(f32::MAX));
+        let summary = controller.run(&mut simulation, &mut rng, settings);
+        assert_eq!(summary.generations, 1);
+
+        let controller = RunController::new().with(StopCondition::Stagnation(2));
+        let summary = controller.run(&mut simulation, &mut rng, settings);
+        assert!(summary.generations >= 3);
+        // Without any condition left, the run still ends after the default generations.
+        let settings = Config::parse_config("[run]\ngenerations = 0");
+        assert_eq!(
+            RunController::config_new(settings).conditions(),
+            [StopCondition::MaxGenerations(100)]
+        );
+    }
+
+    #[derive(Default)]
+    struct Counter {
+        steps: usize,
+        eaten: usize,
+        deaths: usize,
+        generations: Vec<f32>,
+        bests: Vec<f32>,
+    }
+
+    impl Observer for Counter {
+        fn on_step(&mut self, _island: usize, _world: &World) {
+            self.steps += 1;
+        }
+        fn on_food_eaten(&mut self, _island: usize, _world: &World, _animal: &Animal) {
+            self.eaten += 1;
+        }
+        fn on_death(&mut self, _island: usize, _world: &World, _animal: &Animal) {
+            self.deaths += 1;
+        }
+        fn on_generation(&mut self, statistics: &ga::Statistics, worlds: &[World]) {
+            assert_eq!(worlds.len(), 2);
+            self.generations.push(statistics.max_fitness);
+        }
+        fn on_new_best(&mut self, _chromosome: &ga::Chromosome, fitness: f32) {
+            self.bests.push(fitness);
+        }
+    }
+
+    #[test]
+    fn test_observers() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let settings = Config::parse_config(&format!("{TEST_CONFIG}\n[islands]\ncount = 2"));
+        let mut simulation = Simulation::random(&mut rng, settings);
+        let counter = Rc::new(RefCell::new(Counter::default()));
+        simulation.add_observer(counter.clone());
+        simulation.add_observer(Counter::default());
+
+        let mut eaten = 0;
+        for _ in 0..3 {
+            let stats = simulation.fast_forward(&mut rng, settings);
+            eaten += (stats.avg_fitness * 20.0).round() as usize;
+        }
+
+        let counter = counter.borrow();
+        assert_eq!(counter.steps, 3 * 51 * 2);
+        assert_eq!(counter.eaten, eaten);
+        assert_eq!(counter.deaths, 3 * 20);
+        assert_eq!(counter.generations.len(), 3);
+        assert!(!counter.bests.is_empty());
+        assert!(counter.bests.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+
+    // Positions of the animals into their third generation over three islands.
+    fn positions(settings: Config) -> Vec<na::Point2<f32>> {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut simulation = Simulation::random(&mut rng, settings);
+        for _ in 0..2 {
+            simulation.fast_forward(&mut rng, settings);
+        }
+        // New generations start from random positions, stepping for a while into the next one.
+        for _ in 0..20 {
+            simulation.step(&mut rng, settings);
+        }
+
+        simulation
+            .worlds()
+            .iter()
+            .flat_map(|world| world.animals().iter().map(Animal::position))
+            .collect()
+    }
+
+    // Stepping worlds and animals over many threads must land on the very same positions as
+    // stepping them over one.
+    #[test_case(""; "exact")]
+    #[test_case("[noise]\nvision = 0.1\ndropout = 0.1\nrotation = 0.1\nlatency = 2"; "noisy")]
+    fn test_deterministic_steps(noise: &str) {
+        let settings =
+            Config::parse_config(&format!("{TEST_CONFIG}\n[islands]\ncount = 3\n{noise}"));
+
+        #[cfg(feature = "parallel")]
+        let (serial, parallel) = {
+            let pool = |threads| {
+                rayon::ThreadPoolBuilder::new()
+                    .num_threads(threads)
+                    .build()
+                    .unwrap()
+            };
+            (
+                pool(1).install(|| positions(settings)),
+                pool(4).install(|| positions(settings)),
+            )
+        };
+        #[cfg(not(feature = "parallel"))]
+        let (serial, parallel) = (positions(settings), positions(settings));
+
+        assert_eq!(serial, parallel);
+    }
+
+    #[test]
+    fn test_obstacles() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let settings = Config::parse_config(&format!(
+            "{TEST_CONFIG}\n[eye]\nobstacles = true\nmode = \"raycast\"\n[rollouts]\ncount = 1"
+        ));
+        let map = Map::parse_map(
+            "
+            [[obstacles]]
+            shape = \"rect\"
+            min = [0.0, 0.0]
+            max = [1.0, 0.5]
+
+            [[obstacles]]
+            shape = \"segment\"
+            start = [0.0, 0.75]
+            end = [1.0, 0.75]
+            ",
+        );
+        let mut simulation = Simulation::random(&mut rng, settings);
+        simulation.set_map(&mut rng, &map);
+
+        let inside = |world: &World| {
+            let foods = world.foods().iter().map(Food::position);
+            let animals = world.animals().iter().map(Animal::position);
+            foods
+                .chain(animals)
+                .any(|position| map.obstacles.iter().any(|o| o.contains(position)))
+        };
+        assert_eq!(simulation.world().obstacles(), &map.obstacles[..]);
+        assert!(!inside(simulation.world()));
+
+        for _ in 0..100 {
+            simulation.step(&mut rng, settings);
+            assert!(!inside(simulation.world()));
+        }
+        // The wall splits the free half, so animals keep bumping into it or the rectangle.
+        let collisions: usize = simulation
+            .world()
+            .animals()
+            .iter()
+            .map(Animal::collisions)
+            .sum();
+        assert!(collisions > 0);
+        // The default penalty makes every bump cost fitness.
+        assert!(simulation.world().animals().iter().all(|animal| {
+            let cost = 1.0 + 0.1 * animal.collisions() as f32;
+            (animal.fitness() - animal.hunger as f32 / cost).abs() < 1e-6
+        }));
+
+        simulation.fast_forward(&mut rng, settings);
+        assert!(!inside(simulation.world()));
+    }
+
+    #[test]
+    fn test_rollouts() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let settings = Config::parse_config(&format!(
+            "{TEST_CONFIG}\n[islands]\ncount = 2\n[rollouts]\ncount = 2\naggregate = \"median\""
+        ));
+        let mut simulation = Simulation::random(&mut rng, settings);
+
+        let stats = simulation.fast_forward(&mut rng, settings);
+
+        assert!(simulation.worlds().iter().all(|w| w.animals().len() == 10));
+        assert_eq!(simulation.island_statistics().len(), 2);
+        // The median of two rollouts falls on halves of food.
+        assert_eq!((stats.max_fitness * 2.0).fract(), 0.0);
+        assert!(stats.max_fitness > 0.0);
+    }
+
+    #[test]
+    fn test_champion() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let settings = Config::parse_config(TEST_CONFIG);
+        let mut simulation = Simulation::random(&mut rng, settings);
+        assert!(simulation.champion("sparrow", settings).is_none());
+
+        let stats = simulation.fast_forward(&mut rng, settings);
+        let champion = simulation.champion("sparrow", settings).unwrap();
+
+        assert_eq!(champion.fitness, stats.max_fitness);
+        assert!(
+            champion
+                .chromosome()
+                .iter()
+                .eq(simulation.generation_best().unwrap().iter())
+        );
     }
 }
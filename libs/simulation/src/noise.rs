@@ -0,0 +1,151 @@
+use crate::*;
+use std::collections::VecDeque;
+
+/// Noise perturbs what an animal senses and how its brain outputs get applied, so that evolved
+/// controllers stay robust to imperfect sensors and actuators.
+#[derive(Debug, Clone)]
+pub struct Noise {
+    vision: f32,   // Standard deviation added to every eye cell
+    dropout: f32,  // Chance of an eye cell to sense nothing
+    speed: f32,    // Standard deviation added to the speed output
+    rotation: f32, // Standard deviation added to the rotation output
+    latency: usize,
+    pending: VecDeque<Vec<f32>>, // Outputs waiting for the latency to pass, oldest first
+}
+
+impl Noise {
+    pub fn config_new(settings: Config) -> Self {
+        let config = settings.noise;
+        assert!((0.0..=1.0).contains(&config.dropout));
+
+        Self {
+            vision: config.vision,
+            dropout: config.dropout,
+            speed: config.speed,
+            rotation: config.rotation,
+            latency: config.latency,
+            pending: VecDeque::with_capacity(config.latency + 1),
+        }
+    }
+
+    /// Returns true if the animal senses and acts exactly.
+    pub fn is_disabled(&self) -> bool {
+        self.vision == 0.0
+            && self.dropout == 0.0
+            && self.speed == 0.0
+            && self.rotation == 0.0
+            && self.latency == 0
+    }
+
+    /// Drops out eye cells, then adds gaussian noise to the remaining ones.
+    pub(crate) fn perturb_vision(&self, rng: &mut dyn RngCore, cells: &mut [f32]) {
+        for cell in cells {
+            if self.dropout > 0.0 && rng.gen_bool(self.dropout as f64) {
+                *cell = 0.0;
+            } else if self.vision > 0.0 {
+                *cell += self.vision * ga::standard_normal(rng);
+            }
+        }
+    }
+
+    /// Adds gaussian noise to the speed and rotation outputs, then delays them by the latency.
+    /// Nothing gets applied until the first outputs come through.
+    pub(crate) fn perturb_output(
+        &mut self,
+        rng: &mut dyn RngCore,
+        mut output: Vec<f32>,
+    ) -> Vec<f32> {
+        if self.speed > 0.0 {
+            output[0] += self.speed * ga::standard_normal(rng);
+        }
+        if self.rotation > 0.0 {
+            output[1] += self.rotation * ga::standard_normal(rng);
+        }
+
+        self.pending.push_back(output);
+        if self.pending.len() > self.latency {
+            self.pending.pop_front().expect("got no pending output")
+        } else {
+            vec![0.0; 2]
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand::SeedableRng;
+
+    fn noise(section: &str) -> Noise {
+        Noise::config_new(Config::parse_config(&format!("[noise]\n{section}")))
+    }
+
+    #[test]
+    fn test_disabled() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut noise = noise("");
+        let mut cells = vec![0.5, 1.0];
+
+        noise.perturb_vision(&mut rng, &mut cells);
+
+        assert!(noise.is_disabled());
+        assert_eq!(cells, vec![0.5, 1.0]);
+        assert_eq!(
+            noise.perturb_output(&mut rng, vec![0.1, 0.2]),
+            vec![0.1, 0.2]
+        );
+    }
+
+    #[test]
+    fn test_perturb_vision() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut cells = vec![1.0; 1000];
+
+        noise("dropout = 1.0").perturb_vision(&mut rng, &mut cells);
+        assert!(cells.iter().all(|cell| *cell == 0.0));
+
+        let mut cells = vec![1.0; 1000];
+        noise("vision = 0.1").perturb_vision(&mut rng, &mut cells);
+        let mean = cells.iter().sum::<f32>() / cells.len() as f32;
+        let std = (cells.iter().map(|cell| (cell - mean).powi(2)).sum::<f32>()
+            / cells.len() as f32)
+            .sqrt();
+        assert!((mean - 1.0).abs() < 0.02);
+        assert!((std - 0.1).abs() < 0.02);
+    }
+
+    #[test]
+    fn test_latency() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut noise = noise("latency = 2");
+
+        assert!(!noise.is_disabled());
+        assert_eq!(
+            noise.perturb_output(&mut rng, vec![1.0, 1.0]),
+            vec![0.0, 0.0]
+        );
+        assert_eq!(
+            noise.perturb_output(&mut rng, vec![2.0, 2.0]),
+            vec![0.0, 0.0]
+        );
+        assert_eq!(
+            noise.perturb_output(&mut rng, vec![3.0, 3.0]),
+            vec![1.0, 1.0]
+        );
+        assert_eq!(
+            noise.perturb_output(&mut rng, vec![4.0, 4.0]),
+            vec![2.0, 2.0]
+        );
+    }
+
+    #[test]
+    fn test_perturb_output() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut noise = noise("rotation = 0.5");
+
+        let output = noise.perturb_output(&mut rng, vec![0.1, 0.2]);
+
+        assert_eq!(output[0], 0.1);
+        assert_ne!(output[1], 0.2);
+    }
+}
@@ -0,0 +1,50 @@
+use crate::*;
+use std::{cell::RefCell, rc::Rc};
+
+/// Observer describes types reacting to the lifecycle of a simulation, such as loggers,
+/// checkpoints or live plots. Every callback does nothing unless overridden, and callbacks about
+/// a world get the index of its island.
+pub trait Observer {
+    /// Called after every step of every world.
+    fn on_step(&mut self, _island: usize, _world: &World) {}
+
+    /// Called whenever an animal eats, once per food.
+    fn on_food_eaten(&mut self, _island: usize, _world: &World, _animal: &Animal) {}
+
+    /// Called for every animal at the end of a generation, right before its offsprings replace
+    /// the population.
+    fn on_death(&mut self, _island: usize, _world: &World, _animal: &Animal) {}
+
+    /// Called once every island evolved, with the statistics over all of them and the worlds
+    /// as evaluated.
+    fn on_generation(&mut self, _statistics: &ga::Statistics, _worlds: &[World]) {}
+
+    /// Called when the fittest animal of a generation beats every animal met so far.
+    fn on_new_best(&mut self, _chromosome: &ga::Chromosome, _fitness: f32) {}
+}
+
+// Shared observers, to be read back once registered.
+impl<O> Observer for Rc<RefCell<O>>
+where
+    O: Observer,
+{
+    fn on_step(&mut self, island: usize, world: &World) {
+        self.borrow_mut().on_step(island, world);
+    }
+
+    fn on_food_eaten(&mut self, island: usize, world: &World, animal: &Animal) {
+        self.borrow_mut().on_food_eaten(island, world, animal);
+    }
+
+    fn on_death(&mut self, island: usize, world: &World, animal: &Animal) {
+        self.borrow_mut().on_death(island, world, animal);
+    }
+
+    fn on_generation(&mut self, statistics: &ga::Statistics, worlds: &[World]) {
+        self.borrow_mut().on_generation(statistics, worlds);
+    }
+
+    fn on_new_best(&mut self, chromosome: &ga::Chromosome, fitness: f32) {
+        self.borrow_mut().on_new_best(chromosome, fitness);
+    }
+}
@@ -0,0 +1,351 @@
+use crate::*;
+use serde::Deserialize;
+
+// Radius of an animal's body, bumping into obstacles as close as it eats.
+pub(crate) const BODY_RADIUS: f32 = 0.01;
+
+// Draws of a free position before giving up, should obstacles cover most of the world.
+const FREE_POSITION_ATTEMPTS: usize = 100;
+
+/// Obstacle represents a static shape of the world, blocking the sight and the way of the
+/// animals.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(from = "ObstacleShape")]
+pub enum Obstacle {
+    Circle {
+        center: na::Point2<f32>,
+        radius: f32,
+    },
+    /// Axis-aligned rectangle.
+    Rect {
+        min: na::Point2<f32>,
+        max: na::Point2<f32>,
+    },
+    /// Wall without thickness.
+    Segment {
+        start: na::Point2<f32>,
+        end: na::Point2<f32>,
+    },
+}
+
+// Obstacle as written in a map, with points as `[x, y]` arrays.
+#[derive(Deserialize)]
+#[serde(tag = "shape", rename_all = "snake_case")]
+enum ObstacleShape {
+    Circle { center: [f32; 2], radius: f32 },
+    Rect { min: [f32; 2], max: [f32; 2] },
+    Segment { start: [f32; 2], end: [f32; 2] },
+}
+
+impl From<ObstacleShape> for Obstacle {
+    fn from(shape: ObstacleShape) -> Self {
+        match shape {
+            ObstacleShape::Circle { center, radius } => Self::circle(center.into(), radius),
+            ObstacleShape::Rect { min, max } => Self::rect(min.into(), max.into()),
+            ObstacleShape::Segment { start, end } => Self::segment(start.into(), end.into()),
+        }
+    }
+}
+
+impl Obstacle {
+    pub fn circle(center: na::Point2<f32>, radius: f32) -> Self {
+        assert!(radius > 0.0);
+        Self::Circle { center, radius }
+    }
+
+    /// Creates the rectangle between two opposite corners.
+    pub fn rect(a: na::Point2<f32>, b: na::Point2<f32>) -> Self {
+        Self::Rect {
+            min: na::Point2::new(a.x.min(b.x), a.y.min(b.y)),
+            max: na::Point2::new(a.x.max(b.x), a.y.max(b.y)),
+        }
+    }
+
+    pub fn segment(start: na::Point2<f32>, end: na::Point2<f32>) -> Self {
+        assert!(start != end);
+        Self::Segment { start, end }
+    }
+
+    /// Returns true if the point lies inside the obstacle, never for walls.
+    pub fn contains(&self, point: na::Point2<f32>) -> bool {
+        self.signed_distance(point).0 < 0.0
+    }
+
+    /// Returns the distance along the ray to where it first hits the obstacle, if it does.
+    pub fn ray_hit(&self, origin: na::Point2<f32>, direction: na::Vector2<f32>) -> Option<f32> {
+        match *self {
+            Self::Circle { center, radius } => ray_circle(origin, direction, center, radius),
+            Self::Rect { min, max } => ray_rect(origin, direction, min, max),
+            Self::Segment { start, end } => ray_segment(origin, direction, start, end),
+        }
+    }
+
+    /// Returns where a body of given radius ends up once pushed out of the obstacle along the
+    /// shortest way, if it overlaps.
+    pub fn push_out(&self, point: na::Point2<f32>, radius: f32) -> Option<na::Point2<f32>> {
+        let (distance, normal) = self.signed_distance(point);
+        (distance < radius).then(|| point + normal * (radius - distance))
+    }
+
+    // Distance from the point to the obstacle's surface, negative inside, along with the
+    // direction leading out of it.
+    fn signed_distance(&self, point: na::Point2<f32>) -> (f32, na::Vector2<f32>) {
+        match *self {
+            Self::Circle { center, radius } => {
+                let offset = point - center;
+                let normal = offset.try_normalize(0.0).unwrap_or_else(na::Vector2::y);
+                (offset.norm() - radius, normal)
+            }
+            Self::Rect { min, max } => {
+                let inside =
+                    (min.x..=max.x).contains(&point.x) && (min.y..=max.y).contains(&point.y);
+                if !inside {
+                    let closest =
+                        na::Point2::new(point.x.clamp(min.x, max.x), point.y.clamp(min.y, max.y));
+                    let offset = point - closest;
+                    return (offset.norm(), offset.normalize());
+                }
+
+                // Leaving through the nearest side.
+                [
+                    (point.x - min.x, -na::Vector2::x()),
+                    (max.x - point.x, na::Vector2::x()),
+                    (point.y - min.y, -na::Vector2::y()),
+                    (max.y - point.y, na::Vector2::y()),
+                ]
+                .into_iter()
+                .min_by(|(a, _), (b, _)| a.total_cmp(b))
+                .map(|(depth, normal)| (-depth, normal))
+                .expect("got a rectangle without sides")
+            }
+            Self::Segment { start, end } => {
+                let closest = closest_on_segment(point, start, end);
+                let offset = point - closest;
+                let along = end - start;
+                let normal = offset
+                    .try_normalize(0.0)
+                    .unwrap_or_else(|| na::Vector2::new(-along.y, along.x).normalize());
+                (offset.norm(), normal)
+            }
+        }
+    }
+}
+
+/// Map represents the static layout of a world, read from the `[[obstacles]]` tables of a map
+/// file or of the configuration file itself.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct Map {
+    pub obstacles: Vec<Obstacle>,
+}
+
+impl Map {
+    pub fn parse_map(contents: &str) -> Self {
+        toml::from_str(contents).unwrap_or_else(|_| panic!("unable to parse map"))
+    }
+}
+
+/// Returns a random position outside of every obstacle, or the last one drawn should none be
+/// found.
+pub(crate) fn free_position(rng: &mut dyn RngCore, obstacles: &[Obstacle]) -> na::Point2<f32> {
+    let mut position = rng.r#gen();
+    for _ in 1..FREE_POSITION_ATTEMPTS {
+        if !obstacles.iter().any(|obstacle| obstacle.contains(position)) {
+            break;
+        }
+        position = rng.r#gen();
+    }
+    position
+}
+
+// Distance along a ray, of unit direction, to where it enters the circle. Zero if the origin is
+// already inside.
+pub(crate) fn ray_circle(
+    origin: na::Point2<f32>,
+    direction: na::Vector2<f32>,
+    center: na::Point2<f32>,
+    radius: f32,
+) -> Option<f32> {
+    let offset = origin - center;
+    let b = offset.dot(&direction);
+    let c = offset.norm_squared() - radius * radius;
+    if c > 0.0 && b > 0.0 {
+        return None; // Outside and pointing away
+    }
+
+    let discriminant = b * b - c;
+    if discriminant < 0.0 {
+        return None;
+    }
+    Some((-b - discriminant.sqrt()).max(0.0))
+}
+
+// Slab method; distance along the ray to where it enters the rectangle, zero from inside.
+fn ray_rect(
+    origin: na::Point2<f32>,
+    direction: na::Vector2<f32>,
+    min: na::Point2<f32>,
+    max: na::Point2<f32>,
+) -> Option<f32> {
+    let mut near = 0.0_f32;
+    let mut far = f32::INFINITY;
+
+    for axis in 0..2 {
+        if direction[axis] == 0.0 {
+            if origin[axis] < min[axis] || origin[axis] > max[axis] {
+                return None; // Parallel to the slab and out of it
+            }
+            continue;
+        }
+        let a = (min[axis] - origin[axis]) / direction[axis];
+        let b = (max[axis] - origin[axis]) / direction[axis];
+        near = near.max(a.min(b));
+        far = far.min(a.max(b));
+    }
+    (near <= far).then_some(near)
+}
+
+fn ray_segment(
+    origin: na::Point2<f32>,
+    direction: na::Vector2<f32>,
+    start: na::Point2<f32>,
+    end: na::Point2<f32>,
+) -> Option<f32> {
+    let along = end - start;
+    let denominator = direction.perp(&along);
+    if denominator == 0.0 {
+        return None; // Parallel, walls without thickness can't be seen edge-on
+    }
+
+    let offset = start - origin;
+    let distance = offset.perp(&along) / denominator;
+    let fraction = offset.perp(&direction) / denominator;
+    (distance >= 0.0 && (0.0..=1.0).contains(&fraction)).then_some(distance)
+}
+
+fn closest_on_segment(
+    point: na::Point2<f32>,
+    start: na::Point2<f32>,
+    end: na::Point2<f32>,
+) -> na::Point2<f32> {
+    let along = end - start;
+    let fraction = ((point - start).dot(&along) / along.norm_squared()).clamp(0.0, 1.0);
+    start + along * fraction
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand::SeedableRng;
+
+    fn point(x: f32, y: f32) -> na::Point2<f32> {
+        na::Point2::new(x, y)
+    }
+
+    #[test]
+    fn test_ray_circle() {
+        let obstacle = Obstacle::circle(point(0.5, 0.8), 0.1);
+        let origin = point(0.5, 0.5);
+
+        let hit = obstacle.ray_hit(origin, na::Vector2::y()).unwrap();
+        assert!((hit - 0.2).abs() < 1e-6);
+        assert_eq!(obstacle.ray_hit(origin, -na::Vector2::y()), None);
+        assert_eq!(obstacle.ray_hit(origin, na::Vector2::x()), None);
+        assert_eq!(
+            obstacle.ray_hit(point(0.5, 0.8), na::Vector2::x()),
+            Some(0.0)
+        );
+    }
+
+    #[test]
+    fn test_ray_rect() {
+        let obstacle = Obstacle::rect(point(0.6, 0.4), point(0.8, 0.6));
+        let origin = point(0.5, 0.5);
+
+        let hit = obstacle.ray_hit(origin, na::Vector2::x()).unwrap();
+        assert!((hit - 0.1).abs() < 1e-6);
+        assert_eq!(obstacle.ray_hit(origin, na::Vector2::y()), None);
+        assert_eq!(obstacle.ray_hit(origin, -na::Vector2::x()), None);
+        assert_eq!(
+            obstacle.ray_hit(point(0.7, 0.5), na::Vector2::y()),
+            Some(0.0)
+        );
+    }
+
+    #[test]
+    fn test_ray_segment() {
+        let obstacle = Obstacle::segment(point(0.2, 0.7), point(0.8, 0.7));
+        let origin = point(0.5, 0.5);
+
+        let hit = obstacle.ray_hit(origin, na::Vector2::y()).unwrap();
+        assert!((hit - 0.2).abs() < 1e-6);
+        assert_eq!(obstacle.ray_hit(origin, -na::Vector2::y()), None);
+        assert_eq!(obstacle.ray_hit(origin, na::Vector2::x()), None);
+        assert_eq!(obstacle.ray_hit(point(0.9, 0.5), na::Vector2::y()), None);
+    }
+
+    #[test]
+    fn test_push_out() {
+        let circle = Obstacle::circle(point(0.5, 0.5), 0.1);
+        let pushed = circle.push_out(point(0.55, 0.5), 0.01).unwrap();
+        assert!((pushed - point(0.61, 0.5)).norm() < 1e-6);
+        assert_eq!(circle.push_out(point(0.7, 0.5), 0.01), None);
+
+        // Out of a rectangle through its nearest side.
+        let rect = Obstacle::rect(point(0.4, 0.4), point(0.6, 0.6));
+        let pushed = rect.push_out(point(0.5, 0.58), 0.01).unwrap();
+        assert!((pushed - point(0.5, 0.61)).norm() < 1e-6);
+        assert!(rect.contains(point(0.5, 0.58)));
+
+        // Away from a wall, on the side of the body.
+        let wall = Obstacle::segment(point(0.2, 0.5), point(0.8, 0.5));
+        let pushed = wall.push_out(point(0.3, 0.495), 0.01).unwrap();
+        assert!((pushed - point(0.3, 0.49)).norm() < 1e-6);
+        assert!(!wall.contains(point(0.3, 0.5)));
+    }
+
+    #[test]
+    fn test_parse_map() {
+        let map = Map::parse_map(
+            "
+            [world]
+            num_animals = 5
+
+            [[obstacles]]
+            shape = \"circle\"
+            center = [0.3, 0.5]
+            radius = 0.1
+
+            [[obstacles]]
+            shape = \"rect\"
+            min = [0.7, 0.8]
+            max = [0.6, 0.2]
+
+            [[obstacles]]
+            shape = \"segment\"
+            start = [0.1, 0.1]
+            end = [0.9, 0.1]
+            ",
+        );
+
+        assert_eq!(
+            map.obstacles,
+            vec![
+                Obstacle::circle(point(0.3, 0.5), 0.1),
+                Obstacle::rect(point(0.6, 0.2), point(0.7, 0.8)),
+                Obstacle::segment(point(0.1, 0.1), point(0.9, 0.1)),
+            ]
+        );
+        assert!(Map::parse_map("").obstacles.is_empty());
+    }
+
+    #[test]
+    fn test_free_position() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let obstacles = [Obstacle::rect(point(0.0, 0.0), point(1.0, 0.9))];
+
+        for _ in 0..10 {
+            assert!(free_position(&mut rng, &obstacles).y > 0.9);
+        }
+    }
+}
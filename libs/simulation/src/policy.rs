@@ -0,0 +1,95 @@
+use crate::*;
+
+/// SteeringPolicy is a hand-written controller that turns towards the brightest eye cell,
+/// used to record demonstrations for pretraining brains.
+#[derive(Debug, Clone, Copy)]
+pub struct SteeringPolicy {
+    speed_accel: f32,
+    rotation_accel: f32,
+}
+
+impl SteeringPolicy {
+    pub fn config_new(settings: Config) -> Self {
+        let config = settings.simulation;
+        Self {
+            speed_accel: config.speed_accel,
+            rotation_accel: config.rotation_accel,
+        }
+    }
+
+    /// Returns the speed and rotation shifts, in the same layout as the brain outputs.
+    pub fn act(&self, eye: &Eye, vision: &[f32]) -> Vec<f32> {
+        // Only the food channel, coming first, is followed.
+        let brightest = vision[..eye.cells()]
+            .iter()
+            .enumerate()
+            .filter(|(_, energy)| **energy > 0.0)
+            .max_by(|(_, a), (_, b)| a.total_cmp(b));
+
+        let Some((cell_idx, energy)) = brightest else {
+            // Nothing in sight, keep on cruising.
+            return vec![0.0, 0.0];
+        };
+
+        // Inverse of the eye's cell indexing; centre of the cell relative to the boid's rotation.
+        let cell_angle = (cell_idx as f32 + 0.5) / eye.cells() as f32;
+        let angle = (cell_angle - 0.5) * eye.fov_angle();
+
+        vec![
+            (self.speed_accel * energy).min(self.speed_accel),
+            angle.clamp(-self.rotation_accel, self.rotation_accel),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand::SeedableRng;
+    use std::f32::consts::FRAC_PI_2;
+
+    #[test]
+    fn test_act() {
+        let policy = SteeringPolicy::config_new(Config::parse_config(""));
+        let eye = Eye::new(&1.0, &FRAC_PI_2, &5);
+
+        assert_eq!(policy.act(&eye, &[0.0; 5]), vec![0.0, 0.0]);
+
+        let action = policy.act(&eye, &[0.0, 0.0, 0.5, 0.0, 0.0]);
+        assert_eq!(action, vec![0.025, 0.0]);
+
+        let left = policy.act(&eye, &[0.0, 0.0, 0.0, 0.0, 1.0]);
+        let right = policy.act(&eye, &[1.0, 0.0, 0.0, 0.0, 0.0]);
+        assert!(left[1] > 0.0);
+        assert!(right[1] < 0.0);
+    }
+
+    #[test]
+    fn test_pretrained_turns() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let policy = SteeringPolicy::config_new(Config::parse_config(""));
+        let eye = Eye::new(&1.0, &FRAC_PI_2, &5);
+        let mut brain = Brain::random(&mut rng, &eye, &Senses::new());
+
+        // A single food in every cell, at several distances.
+        let vision = |cell: usize, energy: f32| {
+            let mut vision = vec![0.0; eye.inputs()];
+            vision[cell] = energy;
+            vision
+        };
+        let samples: Vec<_> = (0..eye.cells())
+            .flat_map(|cell| [0.2, 0.4, 0.6, 0.8, 1.0].map(|energy| vision(cell, energy)))
+            .map(|vision| {
+                let action = policy.act(&eye, &vision);
+                (vision, action)
+            })
+            .collect();
+        brain.pretrain(&mut rng, &mut nn::Adam::new(0.01), &samples, 500, 5);
+
+        // Rotation targets are negative on the right, which the linear outputs can reach.
+        let left = brain.network().propagate(vision(eye.cells() - 1, 0.7));
+        let right = brain.network().propagate(vision(0, 0.7));
+        assert!(left[1] > 0.0, "got {left:?}");
+        assert!(right[1] < 0.0, "got {right:?}");
+    }
+}
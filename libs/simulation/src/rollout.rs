@@ -0,0 +1,151 @@
+use crate::*;
+
+impl RolloutAggregate {
+    /// Combines the fitness of an animal over every rollout.
+    pub fn aggregate(&self, fitness: &[f32]) -> f32 {
+        assert!(!fitness.is_empty());
+
+        match self {
+            Self::Mean => fitness.iter().sum::<f32>() / fitness.len() as f32,
+            Self::Median => {
+                let mut sorted = fitness.to_vec();
+                sorted.sort_by(|a, b| a.total_cmp(b));
+                let middle = sorted.len() / 2;
+                if sorted.len().is_multiple_of(2) {
+                    (sorted[middle - 1] + sorted[middle]) / 2.0
+                } else {
+                    sorted[middle]
+                }
+            }
+            Self::Worst => fitness.iter().copied().fold(f32::INFINITY, f32::min),
+        }
+    }
+}
+
+/// Rollouts evaluates chromosomes in headless worlds apart from the displayed ones, each with
+/// its own food layout, smoothing out the luck of a single generation.
+#[derive(Debug, Clone)]
+pub struct Rollouts {
+    count: usize,
+    aggregate: RolloutAggregate,
+    steps: usize, // Steps of a rollout, as many as a generation
+    obstacles: Vec<Obstacle>,
+}
+
+impl Rollouts {
+    pub fn config_new(settings: Config) -> Self {
+        Self {
+            count: settings.rollouts.count,
+            aggregate: settings.rollouts.aggregate,
+            steps: settings.simulation.max_generation + 1,
+            obstacles: Vec::new(),
+        }
+    }
+
+    /// Places the obstacles of the map in the rollouts' worlds.
+    pub fn with_map(mut self, map: &Map) -> Self {
+        self.obstacles = map.obstacles.clone();
+        self
+    }
+
+    /// Returns true if the fitness comes from the displayed worlds only.
+    pub fn is_disabled(&self) -> bool {
+        self.count == 0
+    }
+
+    /// Draws the seed of every rollout, to be shared by the islands of a generation.
+    pub fn seeds(&self, rng: &mut dyn RngCore) -> Vec<u64> {
+        (0..self.count).map(|_| rng.r#gen()).collect()
+    }
+
+    /// Returns the aggregated fitness of every chromosome, evaluated together in one world per
+    /// seed, where the seed places both the foods and the animals.
+    pub fn evaluate(
+        &self,
+        seeds: &[u64],
+        settings: Config,
+        chromosomes: &[ga::Chromosome],
+    ) -> Vec<f32> {
+        #[cfg(feature = "parallel")]
+        let seeds = seeds.par_iter();
+        #[cfg(not(feature = "parallel"))]
+        let seeds = seeds.iter();
+        let rollouts: Vec<Vec<f32>> = seeds
+            .map(|&seed| self.rollout(seed, settings, chromosomes))
+            .collect();
+
+        (0..chromosomes.len())
+            .map(|animal| {
+                let fitness: Vec<f32> = rollouts.iter().map(|rollout| rollout[animal]).collect();
+                self.aggregate.aggregate(&fitness)
+            })
+            .collect()
+    }
+
+    fn rollout(&self, seed: u64, settings: Config, chromosomes: &[ga::Chromosome]) -> Vec<f32> {
+        let mut rng = ChaCha8Rng::seed_from_u64(seed);
+        let mut world = World::from_chromosomes(&mut rng, settings, chromosomes);
+        world.set_obstacles(&mut rng, self.obstacles.clone());
+        for _ in 0..self.steps {
+            Simulation::step_world(&mut world, &settings.simulation, &mut rng);
+        }
+
+        world.animals.iter().map(Animal::fitness).collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_aggregate() {
+        let fitness = [4.0, 1.0, 3.0, 8.0];
+
+        assert_eq!(RolloutAggregate::Mean.aggregate(&fitness), 4.0);
+        assert_eq!(RolloutAggregate::Median.aggregate(&fitness), 3.5);
+        assert_eq!(RolloutAggregate::Median.aggregate(&fitness[..3]), 3.0);
+        assert_eq!(RolloutAggregate::Worst.aggregate(&fitness), 1.0);
+    }
+
+    #[test]
+    fn test_evaluate() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let settings = Config::parse_config(
+            "
+            [simulation]
+            max_generation = 50
+
+            [world]
+            num_animals = 10
+            num_foods = 500
+
+            [rollouts]
+            count = 3
+            aggregate = \"worst\"
+            ",
+        );
+        let rollouts = Rollouts::config_new(settings);
+        let chromosomes: Vec<ga::Chromosome> = (0..10)
+            .map(|_| Animal::random(&mut rng, settings).as_chromosome())
+            .collect();
+
+        let seeds = rollouts.seeds(&mut rng);
+        let worst = rollouts.evaluate(&seeds, settings, &chromosomes);
+        assert_eq!(seeds.len(), 3);
+        assert_eq!(worst.len(), 10);
+        assert_eq!(worst, rollouts.evaluate(&seeds, settings, &chromosomes));
+
+        // Every single rollout does at least as well as the worst of them.
+        for &seed in &seeds {
+            let single = rollouts.evaluate(&[seed], settings, &chromosomes);
+            assert!(
+                single
+                    .iter()
+                    .zip(&worst)
+                    .all(|(single, worst)| single >= worst)
+            );
+        }
+    }
+}
@@ -0,0 +1,154 @@
+use crate::*;
+use std::time::Duration;
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Instant;
+
+/// StopCondition describes when a run is done, checked after every generation.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StopCondition {
+    /// Stops after given number of generations.
+    MaxGenerations(usize),
+    /// Stops once the best fitness reaches given value.
+    TargetFitness(f32),
+    /// Stops after given number of generations without improving the best fitness.
+    Stagnation(usize),
+    /// Stops once the run took longer than given duration. Unavailable on wasm32, which has no
+    /// clock to read.
+    #[cfg(not(target_arch = "wasm32"))]
+    WallClock(Duration),
+    /// Stops once the genetic diversity falls below given value.
+    DiversityFloor(f32),
+}
+
+/// RunSummary reports how a run went, along with the fittest chromosome met.
+#[derive(Debug, Clone)]
+pub struct RunSummary {
+    pub generations: usize,
+    pub best_fitness: f32,
+    pub best_chromosome: ga::Chromosome,
+    pub stop_condition: StopCondition, // The condition ending the run
+    pub elapsed: Duration,             // Always zero on wasm32
+    pub statistics: ga::Statistics,    // Of the last generation
+}
+
+/// RunController evolves a simulation generation after generation, until any of its stop
+/// conditions is met.
+#[derive(Debug, Clone, Default)]
+pub struct RunController {
+    conditions: Vec<StopCondition>,
+}
+
+impl RunController {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a controller with the conditions set in the settings, falling back to the default
+    /// number of generations when none of them can end the run.
+    pub fn config_new(settings: Config) -> Self {
+        let config = settings.run;
+        let mut controller = Self::new();
+
+        if config.generations > 0 {
+            controller = controller.with(StopCondition::MaxGenerations(config.generations));
+        }
+        if config.target_fitness > 0.0 {
+            controller = controller.with(StopCondition::TargetFitness(config.target_fitness));
+        }
+        if config.stagnation > 0 {
+            controller = controller.with(StopCondition::Stagnation(config.stagnation));
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        if config.time_budget > 0.0 {
+            let budget = Duration::from_secs_f32(config.time_budget);
+            controller = controller.with(StopCondition::WallClock(budget));
+        }
+        if config.diversity_floor > 0.0 {
+            controller = controller.with(StopCondition::DiversityFloor(config.diversity_floor));
+        }
+        // Such as a lone time budget on wasm32, which the run would never meet.
+        if controller.conditions.is_empty() {
+            let generations = RunConfig::default().generations;
+            controller = controller.with(StopCondition::MaxGenerations(generations));
+        }
+        controller
+    }
+
+    /// Adds a stop condition, the run ending on the first one met.
+    pub fn with(mut self, condition: StopCondition) -> Self {
+        self.conditions.push(condition);
+        self
+    }
+
+    pub fn conditions(&self) -> &[StopCondition] {
+        &self.conditions
+    }
+
+    /// Evolves the simulation until a stop condition is met. Always runs at least one generation.
+    pub fn run(
+        &self,
+        simulation: &mut Simulation,
+        rng: &mut dyn RngCore,
+        settings: Config,
+    ) -> RunSummary {
+        assert!(
+            !self.conditions.is_empty(),
+            "got no stop condition, the run would never end"
+        );
+
+        #[cfg(not(target_arch = "wasm32"))]
+        let start = Instant::now();
+        let mut generations = 0;
+        let mut stagnant = 0;
+        let mut best: Option<(f32, ga::Chromosome)> = None;
+
+        loop {
+            let statistics = simulation.fast_forward(rng, settings);
+            generations += 1;
+
+            let improved = best
+                .as_ref()
+                .is_none_or(|(fitness, _)| statistics.max_fitness > *fitness);
+            if improved {
+                let chromosome = simulation
+                    .generation_best()
+                    .expect("got no best chromosome after evolving")
+                    .clone();
+                best = Some((statistics.max_fitness, chromosome));
+                stagnant = 0;
+            } else {
+                stagnant += 1;
+            }
+
+            let (best_fitness, _) = best.as_ref().expect("got no best chromosome");
+            #[cfg(not(target_arch = "wasm32"))]
+            let elapsed = start.elapsed();
+            #[cfg(target_arch = "wasm32")]
+            let elapsed = Duration::ZERO;
+            let stop_condition =
+                self.conditions
+                    .iter()
+                    .copied()
+                    .find(|condition| match *condition {
+                        StopCondition::MaxGenerations(max) => generations >= max,
+                        StopCondition::TargetFitness(target) => *best_fitness >= target,
+                        StopCondition::Stagnation(window) => stagnant >= window,
+                        #[cfg(not(target_arch = "wasm32"))]
+                        StopCondition::WallClock(budget) => elapsed >= budget,
+                        StopCondition::DiversityFloor(floor) => simulation.diversity() < floor,
+                    });
+
+            if let Some(stop_condition) = stop_condition {
+                let (best_fitness, best_chromosome) = best.expect("got no best chromosome");
+                return RunSummary {
+                    generations,
+                    best_fitness,
+                    best_chromosome,
+                    stop_condition,
+                    elapsed,
+                    statistics,
+                };
+            }
+        }
+    }
+}
@@ -0,0 +1,50 @@
+use crate::*;
+
+/// Selection wraps every selection method available from the configuration, so the genetic
+/// algorithms keep a single concrete type.
+#[derive(Debug, Clone, Copy)]
+pub enum Selection {
+    Roulette(ga::RouletteWheelSelection),
+    Species(ga::SpeciesSelection),
+    Sharing(ga::FitnessSharing),
+}
+
+impl Selection {
+    pub fn config_new(settings: Config) -> Self {
+        let config = settings.selection;
+        match config.method {
+            SelectionKind::Roulette => Self::Roulette(ga::RouletteWheelSelection),
+            SelectionKind::Species => Self::Species(ga::SpeciesSelection::new(
+                ga::Speciation::new(config.species_threshold),
+            )),
+            SelectionKind::Sharing => Self::Sharing(ga::FitnessSharing::new(
+                config.sharing_sigma,
+                config.sharing_alpha,
+            )),
+        }
+    }
+}
+
+impl ga::SelectionMethod for Selection {
+    fn weights<I>(&self, population: &[I]) -> Vec<f32>
+    where
+        I: ga::Individual,
+    {
+        match self {
+            Self::Roulette(selection) => selection.weights(population),
+            Self::Species(selection) => selection.weights(population),
+            Self::Sharing(selection) => selection.weights(population),
+        }
+    }
+
+    fn select<'a, I>(&self, rng: &mut dyn RngCore, population: &'a [I], weights: &[f32]) -> &'a I
+    where
+        I: ga::Individual,
+    {
+        match self {
+            Self::Roulette(selection) => selection.select(rng, population, weights),
+            Self::Species(selection) => selection.select(rng, population, weights),
+            Self::Sharing(selection) => selection.select(rng, population, weights),
+        }
+    }
+}
@@ -0,0 +1,345 @@
+use crate::*;
+use std::fmt;
+
+// Part of an animal's call left after each step, calling again at full volume when eating.
+pub(crate) const CALL_DECAY: f32 = 0.5;
+
+/// Surroundings represents what the animals of a world can sense, as it was before the step.
+#[derive(Debug, Clone)]
+pub struct Surroundings<'a> {
+    pub foods: &'a [Food],
+    pub obstacles: &'a [Obstacle],
+    pub positions: Vec<na::Point2<f32>>,
+    pub calls: Vec<f32>, // Volume of every animal's call, in the order of the positions
+}
+
+impl<'a> Surroundings<'a> {
+    pub fn new(foods: &'a [Food], obstacles: &'a [Obstacle], animals: &[Animal]) -> Self {
+        Self {
+            foods,
+            obstacles,
+            positions: animals.iter().map(Animal::position).collect(),
+            calls: animals.iter().map(Animal::call).collect(),
+        }
+    }
+
+    // Positions and calls of every animal but the one at given index, even those sharing its
+    // position.
+    fn others(&self, index: usize) -> impl Iterator<Item = (na::Point2<f32>, f32)> + '_ {
+        self.positions
+            .iter()
+            .copied()
+            .zip(self.calls.iter().copied())
+            .enumerate()
+            .filter(move |(other, _)| *other != index)
+            .map(|(_, other)| other)
+    }
+
+    /// Returns the positions of every animal but the one at given index.
+    pub fn other_positions(&self, index: usize) -> Vec<na::Point2<f32>> {
+        self.others(index).map(|(position, _)| position).collect()
+    }
+}
+
+/// Sense describes an input of the brain besides the eye, appended after the vision cells.
+pub trait Sense: fmt::Debug + Send + Sync {
+    /// Name of the sense's inputs in rendered brains.
+    fn name(&self) -> &'static str;
+
+    /// Returns the number of inputs the sense feeds the brain with.
+    fn inputs(&self) -> usize;
+
+    /// Returns exactly `inputs()` values sensed by the animal, found at `index` among the
+    /// surroundings' animals.
+    fn sense(&self, animal: &Animal, index: usize, surroundings: &Surroundings) -> Vec<f32>;
+}
+
+/// Proprioception senses the animal's own speed, relative to the maximum speed, and heading.
+#[derive(Debug, Clone, Copy)]
+pub struct Proprioception {
+    speed_max: f32,
+}
+
+impl Sense for Proprioception {
+    fn name(&self) -> &'static str {
+        "proprioception"
+    }
+
+    fn inputs(&self) -> usize {
+        3
+    }
+
+    fn sense(&self, animal: &Animal, _index: usize, _surroundings: &Surroundings) -> Vec<f32> {
+        let heading = animal.rotation().angle();
+        vec![
+            animal.speed() / self.speed_max,
+            heading.sin(),
+            heading.cos(),
+        ]
+    }
+}
+
+/// Smell senses the gradient of food scent within range, pointing towards where food is the
+/// closest and densest, relative to the animal's heading.
+#[derive(Debug, Clone, Copy)]
+pub struct Smell {
+    range: f32,
+}
+
+impl Sense for Smell {
+    fn name(&self) -> &'static str {
+        "smell"
+    }
+
+    fn inputs(&self) -> usize {
+        2
+    }
+
+    fn sense(&self, animal: &Animal, _index: usize, surroundings: &Surroundings) -> Vec<f32> {
+        let position = animal.position();
+        let gradient = surroundings
+            .foods
+            .iter()
+            .map(|food| food.position() - position)
+            .filter(|vec| vec.norm() > 0.0 && vec.norm() < self.range)
+            .fold(na::Vector2::zeros(), |acc, vec| {
+                let scent = (self.range - vec.norm()) / self.range;
+                acc + vec.normalize() * scent
+            });
+
+        // Rotating back to the animal's frame, where it points upward.
+        let gradient = animal.rotation().inverse() * gradient;
+        vec![gradient.x, gradient.y]
+    }
+}
+
+/// Hearing senses the calls of the other animals within range, with a left and a right ear.
+/// Animals call whenever they eat.
+#[derive(Debug, Clone, Copy)]
+pub struct Hearing {
+    range: f32,
+}
+
+impl Sense for Hearing {
+    fn name(&self) -> &'static str {
+        "hearing"
+    }
+
+    fn inputs(&self) -> usize {
+        2
+    }
+
+    fn sense(&self, animal: &Animal, index: usize, surroundings: &Surroundings) -> Vec<f32> {
+        let position = animal.position();
+        let heading = animal.rotation() * na::Vector2::y();
+        let mut ears = vec![0.0; 2];
+
+        for (other, call) in surroundings.others(index) {
+            let vec = other - position;
+            let distance = vec.norm();
+            if call <= 0.0 || distance >= self.range {
+                continue;
+            }
+
+            let volume = call * (self.range - distance) / self.range;
+            // Positive when the call comes from the left of the heading.
+            let side = heading.perp(&vec);
+            if side > 0.0 {
+                ears[0] += volume;
+            } else if side < 0.0 {
+                ears[1] += volume;
+            } else {
+                ears[0] += volume / 2.0;
+                ears[1] += volume / 2.0;
+            }
+        }
+        ears
+    }
+}
+
+/// Senses represents every sense enabled besides the eye, in a fixed order.
+#[derive(Debug, Default)]
+pub struct Senses {
+    senses: Vec<Box<dyn Sense>>,
+}
+
+impl Senses {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn config_new(settings: Config) -> Self {
+        let config = settings.senses;
+        let mut senses = Self::new();
+
+        if config.proprioception {
+            senses = senses.with(Proprioception {
+                speed_max: settings.simulation.speed_max,
+            });
+        }
+        if config.smell {
+            senses = senses.with(Smell {
+                range: config.smell_range,
+            });
+        }
+        if config.hearing {
+            senses = senses.with(Hearing {
+                range: config.hearing_range,
+            });
+        }
+        senses
+    }
+
+    /// Adds a sense, its inputs coming after the ones of the existing senses.
+    pub fn with(mut self, sense: impl Sense + 'static) -> Self {
+        self.senses.push(Box::new(sense));
+        self
+    }
+
+    /// Returns the number of inputs over every sense.
+    pub fn inputs(&self) -> usize {
+        self.senses.iter().map(|sense| sense.inputs()).sum()
+    }
+
+    /// Returns the name of every input over every sense.
+    pub fn labels(&self) -> Vec<String> {
+        self.senses
+            .iter()
+            .flat_map(|sense| {
+                let name = sense.name();
+                (0..sense.inputs()).map(move |input| format!("{name}[{input}]"))
+            })
+            .collect()
+    }
+
+    pub fn sense(&self, animal: &Animal, index: usize, surroundings: &Surroundings) -> Vec<f32> {
+        self.senses
+            .iter()
+            .flat_map(|sense| {
+                let inputs = sense.sense(animal, index, surroundings);
+                assert_eq!(inputs.len(), sense.inputs());
+                inputs
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand::SeedableRng;
+
+    fn settings() -> Config {
+        Config::parse_config(
+            "
+            [senses]
+            proprioception = true
+            smell = true
+            hearing = true
+            smell_range = 0.5
+            hearing_range = 0.5
+            ",
+        )
+    }
+
+    // Animal at the centre, heading upward.
+    fn animal() -> Animal {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut animal = Animal::random(&mut rng, settings());
+        animal.position = na::Point2::new(0.5, 0.5);
+        animal.rotation = na::Rotation2::new(0.0);
+        animal
+    }
+
+    fn food(x: f32, y: f32) -> Food {
+        Food {
+            position: na::Point2::new(x, y),
+        }
+    }
+
+    #[test]
+    fn test_senses() {
+        let animal = animal();
+        let senses = animal.senses();
+
+        assert_eq!(senses.inputs(), 7);
+        assert_eq!(senses.labels()[0], "proprioception[0]");
+        assert_eq!(senses.labels()[6], "hearing[1]");
+        let surroundings = Surroundings::new(&[], &[], &[]);
+        let inputs = animal.perceive(0, &surroundings);
+        assert_eq!(inputs.len(), animal.eye().inputs() + 7);
+    }
+
+    #[test]
+    fn test_proprioception() {
+        let mut animal = animal();
+        animal.speed = 0.0015;
+        let sense = Proprioception { speed_max: 0.003 };
+
+        let inputs = sense.sense(&animal, 0, &Surroundings::new(&[], &[], &[]));
+
+        assert_eq!(inputs, vec![0.5, 0.0, 1.0]);
+    }
+
+    #[test]
+    fn test_smell() {
+        let animal = animal();
+        let sense = Smell { range: 0.5 };
+        // Food out of range is not smelled.
+        let foods = [food(0.5, 0.75), food(0.0, 0.0)];
+
+        let gradient = sense.sense(&animal, 0, &Surroundings::new(&foods, &[], &[]));
+        assert!(gradient[0].abs() < 1e-6);
+        assert!((gradient[1] - 0.5).abs() < 1e-6);
+
+        // Turned to the left, the food is now on our right.
+        let mut animal = animal;
+        animal.rotation = na::Rotation2::new(FRAC_PI_4 * 2.0);
+        let gradient = sense.sense(&animal, 0, &Surroundings::new(&foods, &[], &[]));
+        assert!((gradient[0] - 0.5).abs() < 1e-6);
+        assert!(gradient[1].abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_hearing() {
+        let animal = animal();
+        let sense = Hearing { range: 0.5 };
+        let surroundings = Surroundings {
+            foods: &[],
+            obstacles: &[],
+            // Ourselves, a caller to the left, a quiet one to the right and one out of range.
+            positions: vec![
+                animal.position(),
+                na::Point2::new(0.25, 0.5),
+                na::Point2::new(0.75, 0.5),
+                na::Point2::new(0.5, 0.0),
+            ],
+            calls: vec![1.0, 1.0, 0.0, 1.0],
+        };
+
+        let ears = sense.sense(&animal, 0, &surroundings);
+
+        assert_eq!(ears, vec![0.5, 0.0]);
+    }
+
+    #[test]
+    fn test_others() {
+        let position = na::Point2::new(0.5, 0.5);
+        let other = na::Point2::new(0.25, 0.5);
+        let surroundings = Surroundings {
+            foods: &[],
+            obstacles: &[],
+            // Two animals bumped into the same place, both still see each other.
+            positions: vec![position, position, other],
+            calls: vec![0.0, 1.0, 0.0],
+        };
+
+        assert_eq!(surroundings.other_positions(0), vec![position, other]);
+        assert_eq!(surroundings.other_positions(1), vec![position, other]);
+        assert_eq!(surroundings.other_positions(2), vec![position, position]);
+        assert_eq!(
+            surroundings.others(0).map(|(_, call)| call).sum::<f32>(),
+            1.0
+        );
+    }
+}
@@ -14,15 +14,17 @@ impl Swarm {
         // * Lower diversity in fitness induce more inertia (exploration)
 
         // Add a low constant at denominator to prevent undefined numbers
-        if max_fitness == 0.0 {
-            let max_fitness = max_fitness + 1.0;
-        }
+        let max_fitness = if max_fitness == 0.0 {
+            max_fitness + 1.0
+        } else {
+            max_fitness
+        };
         MAX_INERTIA - (fitness_std / max_fitness) * (MAX_INERTIA - MIN_INERTIA)
     }
 
     pub fn calc_social(
         rng: &mut dyn RngCore,
-        global_best: na::Point2<f32>,
+        _global_best: na::Point2<f32>,
         current: na::Point2<f32>,
     ) -> na::Point2<f32> {
         let random_num = rng.gen_range(-1.0..=1.0);
@@ -38,3 +40,16 @@ impl Swarm {
         na::Point2::from(COGNITIVE_COEF * random_num * (best_position.coords - current.coords))
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_calc_inertia_without_fitness() {
+        // A generation that found no food must not divide by zero.
+        assert_eq!(Swarm::calc_inertia(0.0, 0.0), MAX_INERTIA);
+        let inertia = Swarm::calc_inertia(0.0, 0.5);
+        assert!((inertia - 0.55).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,156 @@
+use crate::*;
+
+/// Trail records where and how an animal moved during a generation, to be summarized as
+/// behaviour descriptors for novelty search and MAP-Elites.
+#[derive(Debug, Clone)]
+pub struct Trail {
+    descriptor: DescriptorKind,
+    checkpoints: Vec<na::Point2<f32>>,
+    max_checkpoints: usize,
+    checkpoint_interval: usize, // Steps between two checkpoints
+    coverage: Vec<bool>,        // Visited cells of a `grid` by `grid` grid, row by row
+    grid: usize,
+    steps: usize,
+    speed_sum: f32,
+    turning_sum: f32, // Sum of absolute rotation shifts
+    spread_sum: f32,  // Sum of distances to the flock's centre
+}
+
+impl Trail {
+    pub fn config_new(settings: Config) -> Self {
+        let config = settings.novelty;
+        assert!(config.checkpoints > 0);
+        assert!(config.grid > 0);
+
+        Self {
+            descriptor: config.descriptor,
+            checkpoints: Vec::with_capacity(config.checkpoints),
+            max_checkpoints: config.checkpoints,
+            checkpoint_interval: (settings.simulation.max_generation / config.checkpoints).max(1),
+            coverage: vec![false; config.grid * config.grid],
+            grid: config.grid,
+            steps: 0,
+            speed_sum: 0.0,
+            turning_sum: 0.0,
+            spread_sum: 0.0,
+        }
+    }
+
+    /// Records the position and speed of the animal after a step.
+    pub(crate) fn record(&mut self, position: na::Point2<f32>, speed: f32) {
+        self.steps += 1;
+        self.speed_sum += speed;
+        if self.steps.is_multiple_of(self.checkpoint_interval)
+            && self.checkpoints.len() < self.max_checkpoints
+        {
+            self.checkpoints.push(position);
+        }
+
+        let cell = |coord: f32| ((coord * self.grid as f32) as usize).min(self.grid - 1);
+        self.coverage[cell(position.y) * self.grid + cell(position.x)] = true;
+    }
+
+    /// Records the rotation shift the animal steered by.
+    pub(crate) fn record_turn(&mut self, angle: f32) {
+        self.turning_sum += angle.abs();
+    }
+
+    /// Records how far the animal strayed from the flock's centre.
+    pub(crate) fn record_spread(&mut self, spread: f32) {
+        self.spread_sum += spread;
+    }
+
+    /// Forgets everything recorded so far.
+    pub(crate) fn reset(&mut self) {
+        self.checkpoints.clear();
+        self.coverage.fill(false);
+        self.steps = 0;
+        self.speed_sum = 0.0;
+        self.turning_sum = 0.0;
+        self.spread_sum = 0.0;
+    }
+
+    /// Returns the average speed and turning rate per step, as the dimensions of MAP-Elites.
+    pub fn motion(&self) -> ga::BehaviourDescriptor {
+        let steps = self.steps.max(1) as f32;
+        ga::BehaviourDescriptor::new(vec![self.speed_sum / steps, self.turning_sum / steps])
+    }
+
+    /// Returns the energy spent, as the sum of speeds, and the average distance to the flock's
+    /// centre.
+    pub fn effort(&self) -> (f32, f32) {
+        (self.speed_sum, self.spread_sum / self.steps.max(1) as f32)
+    }
+
+    /// Summarizes the trail ending at given position as the configured behaviour descriptor.
+    pub fn descriptor(&self, position: na::Point2<f32>) -> ga::BehaviourDescriptor {
+        match self.descriptor {
+            DescriptorKind::FinalPosition => {
+                ga::BehaviourDescriptor::new(vec![position.x, position.y])
+            }
+            DescriptorKind::Trajectory => {
+                // Checkpoints not reached yet are stuck at the current position.
+                let missing = self.max_checkpoints - self.checkpoints.len();
+                self.checkpoints
+                    .iter()
+                    .chain(std::iter::repeat_n(&position, missing))
+                    .flat_map(|point| [point.x, point.y])
+                    .collect()
+            }
+            DescriptorKind::Coverage => self
+                .coverage
+                .iter()
+                .map(|&visited| if visited { 1.0 } else { 0.0 })
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn trail(descriptor: &str) -> Trail {
+        let settings = Config::parse_config(&format!(
+            "[simulation]\nmax_generation = 4\n[novelty]\ndescriptor = \"{descriptor}\"\ncheckpoints = 2\ngrid = 2"
+        ));
+        let mut trail = Trail::config_new(settings);
+        for position in [[0.1, 0.1], [0.2, 0.2], [0.9, 0.1]] {
+            trail.record(na::Point2::from(position), 0.002);
+            trail.record_turn(-0.3);
+        }
+        trail
+    }
+
+    #[test]
+    fn final_position() {
+        let descriptor = trail("final_position").descriptor(na::Point2::new(0.9, 0.1));
+        assert_eq!(descriptor.features(), &[0.9, 0.1]);
+    }
+
+    #[test]
+    fn trajectory() {
+        let descriptor = trail("trajectory").descriptor(na::Point2::new(0.9, 0.1));
+        assert_eq!(descriptor.features(), &[0.2, 0.2, 0.9, 0.1]);
+    }
+
+    #[test]
+    fn coverage() {
+        let mut trail = trail("coverage");
+        let descriptor = trail.descriptor(na::Point2::new(0.9, 0.1));
+        assert_eq!(descriptor.features(), &[1.0, 1.0, 0.0, 0.0]);
+
+        trail.reset();
+        let descriptor = trail.descriptor(na::Point2::new(0.9, 0.1));
+        assert_eq!(descriptor.features(), &[0.0; 4]);
+    }
+
+    #[test]
+    fn motion() {
+        let mut trail = trail("final_position");
+        assert_eq!(trail.motion().features(), &[0.002, 0.3]);
+
+        trail.reset();
+        assert_eq!(trail.motion().features(), &[0.0, 0.0]);
+    }
+}
@@ -4,7 +4,7 @@ use crate::*;
 pub struct World {
     pub(crate) animals: Vec<Animal>,
     pub(crate) foods: Vec<Food>,
-    pub(crate) config: WorldConfig,
+    pub(crate) obstacles: Vec<Obstacle>,
 }
 
 impl World {
@@ -17,15 +17,67 @@ impl World {
         Self {
             animals,
             foods,
-            config,
+            obstacles: Vec::new(),
         }
     }
+
+    /// Creates a world of animals decoded from given chromosomes, among random foods.
+    pub(crate) fn from_chromosomes(
+        rng: &mut dyn RngCore,
+        settings: Config,
+        chromosomes: &[ga::Chromosome],
+    ) -> Self {
+        let animals = chromosomes
+            .iter()
+            .map(|chromosome| Animal::from_chromosome(rng, settings, chromosome.clone()))
+            .collect();
+        let foods = (0..settings.world.num_foods)
+            .map(|_| Food::random(rng))
+            .collect();
+        Self {
+            animals,
+            foods,
+            obstacles: Vec::new(),
+        }
+    }
+
     pub fn animals(&self) -> &[Animal] {
         &self.animals
     }
     pub fn foods(&self) -> &[Food] {
         &self.foods
     }
+    pub fn obstacles(&self) -> &[Obstacle] {
+        &self.obstacles
+    }
+
+    /// Places the obstacles, moving away the foods and animals lying inside.
+    pub(crate) fn set_obstacles(&mut self, rng: &mut dyn RngCore, obstacles: Vec<Obstacle>) {
+        self.obstacles = obstacles;
+        self.free_overlaps(rng);
+    }
+
+    /// Moves the foods and animals lying inside an obstacle to random free positions.
+    pub(crate) fn free_overlaps(&mut self, rng: &mut dyn RngCore) {
+        let obstacles = &self.obstacles;
+        let overlaps = |position: na::Point2<f32>| obstacles.iter().any(|o| o.contains(position));
+
+        for food in &mut self.foods {
+            if overlaps(food.position) {
+                food.position = free_position(rng, obstacles);
+            }
+        }
+        for animal in &mut self.animals {
+            if overlaps(animal.position) {
+                animal.position = free_position(rng, obstacles);
+            }
+        }
+    }
+
+    // Positions of the animals as they were before the step, sensed by the animals channel.
+    pub(crate) fn positions(&self) -> Vec<na::Point2<f32>> {
+        self.animals.iter().map(Animal::position).collect()
+    }
 
     // Implementation of Craig Reynold's Boid Algorithms to simulate flocking
     // on top of our food-searching goal-setting.
@@ -54,9 +106,10 @@ impl World {
     /// position when distance of boids is close.
     pub fn calc_separation(&self, boid: &Animal) -> na::Point2<f32> {
         let boid_pos = boid.position();
-        let separation_units = 0.01;
+        let separation_units = boid.flocking().separation_radius;
         let animals: Vec<_> = self.animals().iter().filter(|a| boid != *a).collect();
-        let new_pos = if animals.is_empty() {
+
+        if animals.is_empty() {
             boid.position()
         } else {
             let update_pos = self.animals().iter().map(|a| a.position()).fold(
@@ -72,8 +125,7 @@ impl World {
                 },
             );
             na::Point2::from(update_pos)
-        };
-        new_pos
+        }
     }
 
     /// Rule 3, Alignment; Averages the velocities of neighbouring boids and to enable
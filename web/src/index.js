@@ -60,6 +60,37 @@ function main() {
         this.fill();
     };
 
+    // Obstacles are given in world coordinates, from 0 to 1 on both axes.
+    CanvasRenderingContext2D.prototype.drawObstacle = function(obstacle, width, height) {
+        this.beginPath();
+        if (obstacle.shape === "circle") {
+            this.arc(
+                obstacle.x * width,
+                obstacle.y * height,
+                obstacle.radius * width,
+                0,
+                2.0 * Math.PI,
+            );
+            this.fillStyle = "#4a4a5e";
+            this.fill();
+        } else if (obstacle.shape === "rect") {
+            this.rect(
+                obstacle.x * width,
+                obstacle.y * height,
+                (obstacle.x2 - obstacle.x) * width,
+                (obstacle.y2 - obstacle.y) * height,
+            );
+            this.fillStyle = "#4a4a5e";
+            this.fill();
+        } else {
+            this.moveTo(obstacle.x * width, obstacle.y * height);
+            this.lineTo(obstacle.x2 * width, obstacle.y2 * height);
+            this.strokeStyle = "#4a4a5e";
+            this.lineWidth = 2.0;
+            this.stroke();
+        }
+    };
+
     // All operation with canvas context are scaled
     ctxt.scale(viewportScale, viewportScale);
 
@@ -70,6 +101,10 @@ function main() {
 
         const world = simulation.world();
 
+        for (const obstacle of world.obstacles) {
+            ctxt.drawObstacle(obstacle, viewport.width, viewport.height);
+        }
+
         for (const food of world.foods) {
             ctxt.drawCircle(
                 food.x * viewport.width,